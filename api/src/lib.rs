@@ -77,3 +77,110 @@ impl From<HitDirection> for IVec2 {
         }
     }
 }
+
+impl State {
+    /// The changes from this state to `next`.
+    pub fn diff(&self, next: &State) -> StateDiff {
+        let entities = next
+            .entities
+            .iter()
+            .filter(|(id, components)| self.entities.get(id) != Some(components))
+            .map(|(id, components)| (*id, components.clone()))
+            .collect();
+
+        let mut removed: Vec<_> = self
+            .entities
+            .keys()
+            .filter(|id| !next.entities.contains_key(id))
+            .copied()
+            .collect();
+        removed.sort();
+
+        StateDiff {
+            base: self.version,
+            version: next.version,
+            entities,
+            removed,
+            inventory: next.inventory.clone(),
+            status: next.status.clone(),
+        }
+    }
+}
+
+impl StateDiff {
+    /// Folds `next`, which applies to this diff's version, into this diff.
+    pub fn merge(&mut self, next: StateDiff) {
+        for id in next.removed.iter() {
+            self.entities.remove(id);
+        }
+
+        self.removed.retain(|id| !next.entities.contains_key(id));
+        self.removed.extend(next.removed);
+        self.removed.sort();
+        self.removed.dedup();
+
+        self.entities.extend(next.entities);
+        self.version = next.version;
+        self.inventory = next.inventory;
+        self.status = next.status;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn at(x: i32) -> Components {
+        Components {
+            positions: vec![PositionComponent { x, y: 0 }],
+            ..Default::default()
+        }
+    }
+
+    fn entities(entities: &[(u32, Components)]) -> HashMap<u32, Components> {
+        entities.iter().cloned().collect()
+    }
+
+    fn state(version: u64, entities: &[(u32, Components)]) -> State {
+        State {
+            entities: self::entities(entities),
+            version,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let base = state(1, &[(1, at(0)), (2, at(0)), (3, at(0))]);
+        let next = state(2, &[(1, at(0)), (2, at(1)), (4, at(0))]);
+        let diff = base.diff(&next);
+
+        assert_eq!(diff.base, 1);
+        assert_eq!(diff.version, 2);
+        assert_eq!(diff.entities, entities(&[(2, at(1)), (4, at(0))]));
+        assert_eq!(diff.removed, vec![3]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let states = [
+            state(0, &[(1, at(0)), (2, at(0))]),
+            // Change 1, add 3
+            state(1, &[(1, at(1)), (2, at(0)), (3, at(0))]),
+            // Remove 1 and 2
+            state(2, &[(3, at(0))]),
+            // Re-add 1
+            state(3, &[(1, at(2)), (3, at(0))]),
+        ];
+
+        let mut merged = states[0].diff(&states[1]);
+        merged.merge(states[1].diff(&states[2]));
+        merged.merge(states[2].diff(&states[3]));
+
+        assert_eq!(merged, states[0].diff(&states[3]));
+        assert_eq!(merged.entities, entities(&[(1, at(2)), (3, at(0))]));
+        assert_eq!(merged.removed, vec![2]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Plays a scripted game in-process, without the gRPC server or a UI.
+//!
+//! Usage: `reshock-play [--seed N] [--level NAME] [SCRIPT]`
+//!
+//! `--level` plays the named room asset as a fixed level, see `Settings`.
+//!
+//! The script is read from `SCRIPT`, or from stdin if not given, and consists
+//! of whitespace-separated commands, each optionally followed by a repeat
+//! count, e.g. `left 3 close`. Anything following a `#` is a comment.
+//!
+//! - `up`, `down`, `left`, `right`, `up-left`, `up-right`, `down-left`,
+//!   `down-right`: move, open doors or attack in that direction
+//! - `close`: close a nearby door
+//! - `shoot`: shoot at something in sight
+//! - `shoot-at X Y`: shoot at position `X`, `Y` relative to the player
+//! - `power N`: set the equipped energy weapon to power level `N`
+//! - `reload`: swap the magazine of the equipped projectile weapon
+//! - `eject`: detach the magazine of the equipped projectile weapon
+//! - `pick-up`: pick up an item at the player's position
+//! - `drop ID`, `equip ID`, `unequip ID`: drop, equip or unequip the carried
+//!   item with entity id `ID`
+//! - `inventory`: print the carried items with their entity ids
+//! - `examine X Y`: print what the player sees at position `X`, `Y` relative
+//!   to them
+//! - `god`: toggle god mode
+//! - `render`: print the player's view
+//!
+//! The events and log lines of each action are printed as they happen, and the
+//! player's view once more after the script has ended.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+
+use anyhow::{bail, Context, Result};
+use api::renderable_component::Renderable;
+use reshock_server::game::{
+    Action, DropAction, DwimAction, EjectMagazineAction, EquipAction, Game, GodModeAction,
+    PickUpAction, PowerAction, ReloadAction, Settings, ShootTarget, UnequipAction,
+};
+
+#[derive(Debug, Clone)]
+enum Command {
+    Act(Action),
+    Render,
+    Inventory,
+    Examine(glam::IVec2),
+}
+
+fn parse(script: &str) -> Result<Vec<Command>> {
+    let mut commands = Vec::new();
+
+    for (n, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let mut tokens = line.split_whitespace().peekable();
+
+        while let Some(token) = tokens.next() {
+            let command = match token {
+                "up-left" => Command::Act(Action::Dwim(DwimAction::UpLeft)),
+                "up" => Command::Act(Action::Dwim(DwimAction::Up)),
+                "up-right" => Command::Act(Action::Dwim(DwimAction::UpRight)),
+                "right" => Command::Act(Action::Dwim(DwimAction::Right)),
+                "down-right" => Command::Act(Action::Dwim(DwimAction::DownRight)),
+                "down" => Command::Act(Action::Dwim(DwimAction::Down)),
+                "down-left" => Command::Act(Action::Dwim(DwimAction::DownLeft)),
+                "left" => Command::Act(Action::Dwim(DwimAction::Left)),
+                "close" => Command::Act(Action::Dwim(DwimAction::Close)),
+                "shoot" => Command::Act(Action::Dwim(DwimAction::Shoot(None))),
+                "shoot-at" => {
+                    let mut coordinate = || {
+                        tokens
+                            .next()
+                            .and_then(|s| s.parse().ok())
+                            .with_context(|| format!("Line {}: shoot-at needs a position", n + 1))
+                    };
+                    let (x, y) = (coordinate()?, coordinate()?);
+                    Command::Act(Action::Dwim(DwimAction::Shoot(Some(
+                        ShootTarget::Position(glam::ivec2(x, y)),
+                    ))))
+                }
+                "god" => Command::Act(Action::GodMode(GodModeAction::Intent)),
+                "power" => {
+                    let power = tokens
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .with_context(|| format!("Line {}: power needs a level", n + 1))?;
+                    Command::Act(Action::Power(PowerAction::Intent { power }))
+                }
+                "reload" => Command::Act(Action::Reload(ReloadAction::Intent)),
+                "eject" => Command::Act(Action::EjectMagazine(EjectMagazineAction::Intent)),
+                "pick-up" => Command::Act(Action::PickUp(PickUpAction::Intent { item: None })),
+                "drop" | "equip" | "unequip" => {
+                    let item = tokens
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .with_context(|| format!("Line {}: {} needs an item id", n + 1, token))?;
+                    Command::Act(match token {
+                        "drop" => Action::Drop(DropAction::Intent { item }),
+                        "equip" => Action::Equip(EquipAction::Intent { item }),
+                        _ => Action::Unequip(UnequipAction::Intent { item }),
+                    })
+                }
+                "examine" => {
+                    let mut coordinate = || {
+                        tokens
+                            .next()
+                            .and_then(|s| s.parse().ok())
+                            .with_context(|| format!("Line {}: examine needs a position", n + 1))
+                    };
+                    let (x, y) = (coordinate()?, coordinate()?);
+                    Command::Examine(glam::ivec2(x, y))
+                }
+                "inventory" => Command::Inventory,
+                "render" => Command::Render,
+                _ => bail!("Line {}: unknown command {:?}", n + 1, token),
+            };
+
+            let count = match tokens.peek().and_then(|s| s.parse::<usize>().ok()) {
+                Some(count) => {
+                    tokens.next();
+                    count
+                }
+                None => 1,
+            };
+
+            commands.extend(std::iter::repeat(command).take(count));
+        }
+    }
+
+    Ok(commands)
+}
+
+fn glyph(renderable: Renderable, open: Option<bool>) -> Option<(char, u8)> {
+    // Higher ranks win when several entities share a cell
+    match renderable {
+        Renderable::None => None,
+        Renderable::Floor => Some(('·', 0)),
+        Renderable::Wall => Some(('#', 1)),
+        Renderable::Door => match open {
+            Some(true) => Some(('O', 2)),
+            _ => Some(('o', 2)),
+        },
+        Renderable::Corpse => Some(('%', 3)),
+        Renderable::Melee => Some(('/', 3)),
+        Renderable::ProjectileGun => Some(('}', 3)),
+        Renderable::EnergyGun => Some(('{', 3)),
+        Renderable::Magazine => Some(('=', 3)),
+        Renderable::WallSwitch => Some(('x', 4)),
+        Renderable::Server => Some(('c', 4)),
+        Renderable::ServBot => Some(('b', 5)),
+        Renderable::Human => Some(('@', 5)),
+    }
+}
+
+/// Draws everything the player sees or remembers, relative to the player.
+fn render(state: &api::State) -> String {
+    let mut cells: HashMap<(i32, i32), (char, u8)> = HashMap::new();
+
+    for components in state.entities.values() {
+        let renderable = match components
+            .renderable
+            .as_ref()
+            .and_then(|it| Renderable::from_i32(it.renderable))
+        {
+            Some(it) => it,
+            None => continue,
+        };
+
+        let (c, rank) = match glyph(renderable, components.door.as_ref().map(|it| it.open)) {
+            Some(it) => it,
+            None => continue,
+        };
+
+        for pos in &components.positions {
+            let cell = cells.entry((pos.x, pos.y)).or_insert((c, rank));
+            if rank > cell.1 {
+                *cell = (c, rank);
+            }
+        }
+    }
+
+    let (min_x, max_x, min_y, max_y) = cells.keys().fold(
+        (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+        |(min_x, max_x, min_y, max_y), (x, y)| {
+            (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y))
+        },
+    );
+
+    let mut s = String::new();
+
+    for y in (min_y..=max_y).rev() {
+        let line: String = (min_x..=max_x)
+            .map(|x| cells.get(&(x, y)).map(|(c, _)| *c).unwrap_or(' '))
+            .collect();
+        s.push_str(line.trim_end());
+        s.push('\n');
+    }
+
+    s
+}
+
+fn print_event(event: &api::Event) {
+    match &event.event {
+        Some(api::event::Event::State(api::StateUpdateEvent {
+            state: Some(state), ..
+        })) => {
+            println!("  event State ({} entities)", state.entities.len());
+        }
+        Some(api::event::Event::State(api::StateUpdateEvent {
+            diff: Some(diff), ..
+        })) => {
+            println!(
+                "  event State diff ({} changed, {} removed)",
+                diff.entities.len(),
+                diff.removed.len()
+            );
+        }
+        Some(api::event::Event::Log(_)) => {}
+        Some(it) => println!("  event {:?}", it),
+        None => {}
+    }
+}
+
+fn main() -> Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+
+    let mut args = std::env::args().skip(1);
+    let mut seed = None;
+    let mut level = None;
+    let mut path = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().context("--seed needs a value")?;
+                seed = Some(value.parse().context("--seed must be a number")?);
+            }
+            "--level" => {
+                level = Some(args.next().context("--level needs a value")?);
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => bail!("Usage: reshock-play [--seed N] [--level NAME] [SCRIPT]"),
+        }
+    }
+
+    let script = match &path {
+        Some(path) => fs::read_to_string(path).with_context(|| format!("Reading {}", path))?,
+        None => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s)?;
+            s
+        }
+    };
+
+    let commands = parse(&script)?;
+
+    let mut game = Game::new(Settings { seed, level })?;
+    let state = game.state()?;
+    let mut logged = 0;
+
+    println!("seed {}", state.seed);
+    for entry in state.log.map(|it| it.entries).unwrap_or_default() {
+        println!("> {}", entry);
+        logged += 1;
+    }
+
+    for command in commands {
+        match command {
+            Command::Act(action) => {
+                println!("{:?}", action);
+
+                for event in game.input(action) {
+                    print_event(&event);
+                }
+
+                let log = game.state()?.log.map(|it| it.entries).unwrap_or_default();
+                for entry in log.iter().skip(logged) {
+                    println!("> {}", entry);
+                }
+                logged = log.len();
+            }
+            Command::Render => {
+                print!("{}", render(&game.state()?.state.unwrap_or_default()));
+            }
+            Command::Examine(position) => {
+                for entity in game.examine(position)?.entities {
+                    println!("  {} {}", entity.entity, entity.description);
+                }
+            }
+            Command::Inventory => {
+                for item in game.state()?.state.unwrap_or_default().inventory {
+                    let equipped = if item.equipped { " (equipped)" } else { "" };
+                    println!("  {} {}{}", item.entity, item.name, equipped);
+                }
+            }
+        }
+    }
+
+    print!("{}", render(&game.state()?.state.unwrap_or_default()));
+
+    Ok(())
+}
@@ -6,6 +6,11 @@ const DEFAULTS: &str = include_str!("defaults.toml");
 #[derive(Clone, Debug, Deserialize)]
 pub struct Config {
     pub listen_address: String,
+    pub save_directory: String,
+    pub seed: Option<u64>,
+    pub level: Option<String>,
+    pub journal_directory: String,
+    pub replay: Option<String>,
 }
 
 impl Config {
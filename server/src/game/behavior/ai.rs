@@ -1,8 +1,14 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use bevy_ecs::prelude::*;
+use bevy_hierarchy::Parent;
 
-use crate::game::{component::*, pathfinding::AStar, resource::Deltas, *};
+use crate::game::{
+    component::*,
+    pathfinding::RoomGraph,
+    resource::{Deltas, Random},
+    *,
+};
 
 pub fn behavior(
     action: Res<Action>,
@@ -11,32 +17,55 @@ pub fn behavior(
     ai: Query<(Entity, &AI, &Position, Option<&AIMemory>, Option<&Sight>)>,
     deltas: Res<Deltas>,
     obstacles: Query<&Position, With<Solid>>,
+    gateways: Query<(&Position, &Gateway)>,
+    positions: Query<&Position>,
+    doors: Query<(Entity, &Position, &Door, Option<&Parent>), Without<RoomSpawner>>,
+    kinds: Query<&DoorKind>,
+    locks: Query<&Lock>,
     player: Query<(Entity, &Position), With<Player>>,
+    mut rng: ResMut<Random>,
 ) -> Status {
     let actor = match action.as_ref() {
         Action::AI(it) => it,
-        Action::EndTurn(entity) => {
-            if player.contains(*entity) {
-                for (entity, ..) in ai.iter() {
-                    followups.0.push(Action::AI(entity));
-                }
-            }
-            return Status::Continue;
-        }
         _ => return Status::Continue,
     };
 
-    let mut rng = thread_rng();
-
     let player = player.single();
 
     let (actor, ai, viewer, mem, sight) = ai.get(*actor).unwrap();
 
-    let obstacles: HashSet<_> = obstacles
+    let mut obstacles: HashMap<_, HashSet<_>> =
+        obstacles.iter().fold(HashMap::new(), |mut acc, pos| {
+            acc.entry(pos.room).or_default().insert(pos.coordinates);
+            acc
+        });
+
+    // Closed doors the actor can open, which are only obstacles for as long as
+    // it takes to open them. Bulkhead doors are parts of their parent.
+    let closed: HashMap<Position, Entity> = doors
         .iter()
-        .filter_map(|pos| (pos.room == viewer.room).then_some(pos.coordinates))
+        .filter(|(entity, _, door, parent)| {
+            let door_entity = parent.map(|it| it.get()).unwrap_or(*entity);
+            let locked = locks
+                .iter()
+                .any(|lock| lock.active && lock.locked.contains(&door_entity));
+
+            !door.open && !locked && matches!(kinds.get(door_entity), Ok(kind) if ai.opens(*kind))
+        })
+        .map(|(entity, position, ..)| (*position, entity))
         .collect();
 
+    let mut penalties: HashMap<_, HashMap<_, _>> = HashMap::new();
+    for position in closed.keys() {
+        if let Some(it) = obstacles.get_mut(&position.room) {
+            it.remove(&position.coordinates);
+        }
+        penalties
+            .entry(position.room)
+            .or_default()
+            .insert(position.coordinates, 1.0);
+    }
+
     let (enemy, is_seen) = if sight.is_some_and(|sight| sight.seeing.contains_key(&player.0)) {
         (Some(player.1), true)
     } else if let Some(enemy) = mem.and_then(|mem| mem.enemy.as_ref()) {
@@ -45,19 +74,34 @@ pub fn behavior(
         (None, false)
     };
 
+    let wait = Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::WAIT,
+    });
+
     match (ai, enemy) {
         (AI::None, _) => {
             log::debug!("I'm dumb and can't do shit");
+            followups.0.push(wait);
         }
-        (AI::ServBot, Some(enemy)) if enemy.room == viewer.room => {
-            // TODO get through rooms
-            let astar = AStar::new(obstacles);
-            match astar.search(viewer.coordinates, enemy.coordinates) {
-                Some(path) if path.len() > 1 => {
+        (AI::ServBot, Some(enemy)) => {
+            let graph = RoomGraph::new(gateways.iter().map(|(pos, gateway)| {
+                let twin = positions.get(gateway.twin).unwrap();
+                (*pos, *twin, gateway.direction)
+            }));
+
+            match graph.search(*viewer, *enemy, obstacles, penalties) {
+                Some(path) if !path.is_empty() => {
                     log::debug!("Path to player is {:?}", path);
-                    let delta = path[1] - viewer.coordinates;
+                    let step = path[0];
+                    let delta = step.delta;
 
-                    if !is_seen || path.len() > 2 {
+                    if let Some(target) = closed.get(&step.position) {
+                        reactions.0.push(Action::OpenDoor(OpenDoorAction {
+                            actor,
+                            target: *target,
+                        }));
+                    } else if !is_seen || path.len() > 1 || step.position.room != viewer.room {
                         reactions
                             .0
                             .push(Action::Move(MoveAction::Intent { actor, delta }));
@@ -77,6 +121,7 @@ pub fn behavior(
                             .push(Action::AIMemorize(AIMemorizeAction { actor, memory }));
                     }
                     log::debug!("Can't get to player, sulking");
+                    followups.0.push(wait);
                 }
             }
         }
@@ -85,14 +130,22 @@ pub fn behavior(
                 .0
                 .clone()
                 .into_iter()
-                .filter(|delta| !obstacles.contains(&(viewer.coordinates + *delta)))
-                .choose(&mut rng)
+                .filter(|delta| {
+                    let target = *viewer + *delta;
+
+                    !closed.contains_key(&target)
+                        && !obstacles
+                            .get(&viewer.room)
+                            .is_some_and(|it| it.contains(&target.coordinates))
+                })
+                .choose(&mut *rng)
             {
                 reactions
                     .0
                     .push(Action::Move(MoveAction::Intent { actor, delta }));
             } else {
                 log::debug!("I'm stuck");
+                followups.0.push(wait);
             }
         }
     }
@@ -1,6 +1,6 @@
 use bevy_ecs::prelude::*;
 
-use crate::game::{component::*, Status, *};
+use crate::game::{component::*, resource::Random, Status, *};
 
 fn multiplier(attack: &AttackKind, vulnerable: &VulnerableKind) -> u8 {
     match (attack, vulnerable) {
@@ -98,6 +98,7 @@ pub fn damage(
     vulnerables: Query<&Vulnerable>,
     descriptions: Query<&Description>,
     mut reactions: ResMut<Reactions>,
+    mut rng: ResMut<Random>,
 ) -> Status {
     let DamageAction {
         actor,
@@ -109,8 +110,6 @@ pub fn damage(
         _ => return Status::Continue,
     };
 
-    let mut rng = thread_rng();
-
     let vulnerable = vulnerables.get(*target).unwrap();
 
     let penetration = {
@@ -7,6 +7,7 @@ pub fn behavior(
     alives: Query<&Alive>,
     vulnerables: Query<&Vulnerable>,
     descriptions: Query<&Description>,
+    positions: Query<&Position>,
     mut reactions: ResMut<Reactions>,
 ) -> Status {
     let actor = match action.as_ref() {
@@ -29,5 +30,13 @@ pub fn behavior(
         reactions.0.push(log);
     }
 
+    if let Ok(position) = positions.get(actor) {
+        reactions.0.push(Action::Noise(NoiseAction {
+            actor,
+            source: *position,
+            loudness: noise::DEATH,
+        }));
+    }
+
     Status::Continue
 }
@@ -7,9 +7,11 @@ use crate::game::{component::*, *};
 
 pub fn behavior(
     action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
     mut followups: ResMut<FollowUps>,
     blockers: Query<&Position, With<Solid>>,
     doors: Query<&Position, With<Door>>,
+    kinds: Query<&DoorKind>,
     parents: Query<&Children, With<Door>>,
     children: Query<&Parent, With<Door>>,
     player: Query<(), With<Player>>,
@@ -22,7 +24,24 @@ pub fn behavior(
                     target: **parent,
                 })])
             } else {
-                followups.0.push(Action::EndTurn(*actor));
+                // Bulkhead doors are heard from their first part
+                let source = doors.get(*target).ok().or_else(|| {
+                    let children = parents.get(*target).ok()?;
+                    doors.get(*children.first()?).ok()
+                });
+
+                if let (Some(source), Ok(kind)) = (source, kinds.get(*target)) {
+                    reactions.0.push(Action::Noise(NoiseAction {
+                        actor: *actor,
+                        source: *source,
+                        loudness: noise::door(*kind),
+                    }));
+                }
+
+                followups.0.push(Action::EndTurn(EndTurnAction {
+                    actor: *actor,
+                    cost: cost::DOOR,
+                }));
                 Status::Continue
             }
         }
@@ -51,7 +70,10 @@ pub fn behavior(
 
                     Status::Reject(actions)
                 } else {
-                    followups.0.push(Action::EndTurn(*actor));
+                    followups.0.push(Action::EndTurn(EndTurnAction {
+                        actor: *actor,
+                        cost: cost::DOOR,
+                    }));
                     Status::Continue
                 }
             }
@@ -81,7 +81,7 @@ pub fn close(
 
     let (actor, position) = player.single();
 
-    let neighbors: HashSet<_> = match gateways.iter().find(|(pos, _)| *pos == position) {
+    let neighbors: Vec<_> = match gateways.iter().find(|(pos, _)| *pos == position) {
         Some((pos, gateway)) => {
             let twin = gateways.get(gateway.twin).unwrap().0;
             deltas
@@ -123,24 +123,59 @@ pub fn shoot(
     mut reactions: ResMut<Reactions>,
     player: Query<(Entity, &Sight), With<Player>>,
     npcs: Query<Entity, (With<Vulnerable>, Without<Player>)>,
+    solids: Query<(), With<Solid>>,
 ) -> Status {
-    match action.as_ref() {
-        Action::Dwim(DwimAction::Shoot) => {}
+    let target = match action.as_ref() {
+        Action::Dwim(DwimAction::Shoot(target)) => target,
         _ => return Status::Continue,
     };
 
     let (actor, sight) = player.single();
 
-    match npcs.iter().find(|e| sight.seeing.contains_key(&e)) {
-        Some(target) => {
+    let target = match target {
+        // Without a target, shoot at the closest one in sight.
+        None => npcs
+            .iter()
+            .filter_map(|e| {
+                let distance = sight
+                    .seeing
+                    .get(&e)?
+                    .iter()
+                    .map(|pos| pos.dot(*pos))
+                    .min()?;
+                Some((e, distance))
+            })
+            .min_by_key(|(e, distance)| (*distance, e.id()))
+            .map(|(e, _)| e)
+            .ok_or("No one to shoot at in sight"),
+        Some(ShootTarget::Entity(id)) => sight
+            .seeing
+            .keys()
+            .find(|e| e.id() == *id && **e != actor)
+            .copied()
+            .ok_or("You can't see that target"),
+        // Vulnerable entities at the position go before other solid ones.
+        Some(ShootTarget::Position(pos)) => sight
+            .seeing
+            .iter()
+            .filter(|(e, cells)| {
+                **e != actor && cells.contains(pos) && (npcs.contains(**e) || solids.contains(**e))
+            })
+            .map(|(e, _)| *e)
+            .min_by_key(|e| (!npcs.contains(*e), e.id()))
+            .ok_or("There is nothing to shoot at there"),
+    };
+
+    match target {
+        Ok(target) => {
             reactions
                 .0
                 .push(Action::Shoot(ShootAction::Intent { actor, target }));
 
             Status::Continue
         }
-        None => {
-            let action = Action::Log("No one to shoot at in sight".to_string());
+        Err(reason) => {
+            let action = Action::Log(reason.to_string());
             Status::Reject(vec![action])
         }
     }
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, pathfinding::RoomGraph, *};
+
+pub fn behavior(
+    action: Res<Action>,
+    listeners: Query<(Entity, &AI, &Position), With<AIMemory>>,
+    walls: Query<&Position, With<Wall>>,
+    doors: Query<(&Position, &Door)>,
+    gateways: Query<(&Position, &Gateway)>,
+    positions: Query<&Position>,
+    mut reactions: ResMut<Reactions>,
+) -> Status {
+    let NoiseAction {
+        actor,
+        source,
+        loudness,
+    } = match action.as_ref() {
+        Action::Noise(it) => *it,
+        _ => return Status::Continue,
+    };
+
+    // NPCs don't investigate noise made by themselves or each other, but
+    // they do come to see who died.
+    let npc = loudness != noise::DEATH && listeners.contains(actor);
+    if npc || !listeners.iter().any(|(_, ai, _)| ai.hears()) {
+        return Status::Continue;
+    }
+
+    let graph = RoomGraph::new(gateways.iter().map(|(pos, gateway)| {
+        let twin = positions.get(gateway.twin).unwrap();
+        (*pos, *twin, gateway.direction)
+    }));
+
+    let walls: HashMap<_, HashSet<_>> = walls.iter().fold(HashMap::new(), |mut acc, pos| {
+        acc.entry(pos.room).or_default().insert(pos.coordinates);
+        acc
+    });
+
+    let mut penalties: HashMap<_, HashMap<_, _>> = HashMap::new();
+    for (pos, _) in doors.iter().filter(|(_, door)| !door.open) {
+        *penalties
+            .entry(pos.room)
+            .or_default()
+            .entry(pos.coordinates)
+            .or_default() += noise::CLOSED_DOOR;
+    }
+    for (pos, _) in gateways.iter() {
+        *penalties
+            .entry(pos.room)
+            .or_default()
+            .entry(pos.coordinates)
+            .or_default() += noise::GATEWAY;
+    }
+
+    let heard = graph.spread(source, loudness, walls, penalties);
+
+    for (listener, ai, position) in listeners.iter() {
+        if ai.hears() && heard.contains_key(position) {
+            log::debug!("{:?} heard a noise at {:?}", listener, source);
+
+            reactions.0.push(Action::AIMemorize(AIMemorizeAction {
+                actor: listener,
+                memory: AIMemory {
+                    enemy: Some(source),
+                },
+            }));
+        }
+    }
+
+    Status::Continue
+}
+
+#[cfg(test)]
+mod test {
+    use glam::ivec2;
+
+    use super::*;
+
+    /// All NPCs with their positions, forgetting whatever they saw so far.
+    fn listeners(game: &mut Game) -> Vec<(Entity, Position)> {
+        let listeners: Vec<_> = game
+            .world
+            .query_filtered::<(Entity, &Position), With<AI>>()
+            .iter(&game.world)
+            .map(|(entity, pos)| (entity, *pos))
+            .sorted_by_key(|(_, pos)| pos.coordinates.x)
+            .collect();
+
+        for (entity, _) in listeners.iter() {
+            *game.world.get_mut::<AIMemory>(*entity).unwrap() = Default::default();
+        }
+
+        listeners
+    }
+
+    fn enemy(game: &Game, entity: Entity) -> Option<Position> {
+        game.world.get::<AIMemory>(entity).unwrap().enemy
+    }
+
+    #[test]
+    fn test_gunfire_next_room() {
+        let mut game = Game::with_rooms(
+            "
+#####
+#@··|
+#####",
+            &[r#"+++
+[legend]
+b = { tile = "npc", npc = "serv-bot" }
++++
+#############
+|·····b·····|
+#############"#],
+        );
+
+        let (player, source) = game
+            .world
+            .query_filtered::<(Entity, &Position), With<Player>>()
+            .single(&game.world);
+        let source = *source;
+        let spawner = game
+            .world
+            .query_filtered::<(Entity, &Position), (With<RoomSpawner>, With<Door>)>()
+            .iter(&game.world)
+            .find_map(|(entity, pos)| (pos.coordinates == ivec2(4, 1)).then_some(entity))
+            .unwrap();
+
+        game.input(Action::OpenDoor(OpenDoorAction {
+            actor: player,
+            target: spawner,
+        }));
+
+        let listeners = listeners(&mut game);
+        let (bot, position) = listeners[0];
+        assert_eq!(position.room, RoomId(1));
+
+        game.input(Action::Noise(NoiseAction {
+            actor: player,
+            source,
+            loudness: noise::GUNFIRE,
+        }));
+        assert_eq!(enemy(&game, bot), Some(source));
+    }
+
+    #[test]
+    fn test_death_alerts() {
+        let mut game = Game::with_rooms(
+            r#"+++
+[legend]
+b = { tile = "npc", npc = "serv-bot" }
++++
+#########
+#@··b·b·#
+#########"#,
+            &[],
+        );
+
+        let listeners = listeners(&mut game);
+        let (victim, source) = listeners[0];
+        let (witness, _) = listeners[1];
+
+        game.input(Action::HealthLoss(HealthLossAction {
+            actor: victim,
+            amount: 20,
+        }));
+        assert!(game.world.get::<Alive>(victim).is_none());
+        assert_eq!(enemy(&game, witness), Some(source));
+    }
+}
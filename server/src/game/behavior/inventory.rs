@@ -0,0 +1,255 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, *};
+
+/// Equipment slots, each holding at most one equipped weapon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Melee,
+    Gun,
+}
+
+impl Slot {
+    fn of(melee: Option<&MeleeWeapon>, ranged: Option<&RangedWeapon>) -> Option<Self> {
+        match (melee, ranged) {
+            (Some(_), _) => Some(Self::Melee),
+            (_, Some(_)) => Some(Self::Gun),
+            _ => None,
+        }
+    }
+}
+
+pub fn pick_up(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+    player: Query<(Entity, &Position), With<Player>>,
+    items: Query<(Entity, &Item, &Position)>,
+    descriptions: Query<&Description>,
+) -> Status {
+    let id = match action.as_ref() {
+        Action::PickUp(PickUpAction::Intent { item }) => *item,
+        _ => return Status::Continue,
+    };
+
+    let (actor, position) = player.single();
+
+    let item = match items
+        .iter()
+        .filter(|(entity, item, pos)| {
+            item.owner.is_none() && *pos == position && id.map_or(true, |id| entity.id() == id)
+        })
+        .map(|(entity, ..)| entity)
+        .min_by_key(|entity| entity.id())
+    {
+        Some(it) => it,
+        None => {
+            let action = Action::Log("There is nothing to pick up here".to_string());
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    reactions
+        .0
+        .push(Action::PickUp(PickUpAction::Take { actor, item }));
+    log(&mut reactions, &descriptions, actor, "picks up", item);
+
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::PICK_UP,
+    }));
+
+    Status::Continue
+}
+
+pub fn drop(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+    player: Query<Entity, With<Player>>,
+    items: Query<(Entity, &Item)>,
+    descriptions: Query<&Description>,
+) -> Status {
+    let id = match action.as_ref() {
+        Action::Drop(DropAction::Intent { item }) => *item,
+        _ => return Status::Continue,
+    };
+
+    let actor = player.single();
+
+    let item = match items.iter().find_map(|(entity, item)| {
+        (item.owner == Some(actor) && entity.id() == id).then_some(entity)
+    }) {
+        Some(it) => it,
+        None => {
+            let action = Action::Log("You don't carry that".to_string());
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    reactions
+        .0
+        .push(Action::Drop(DropAction::Put { actor, item }));
+    log(&mut reactions, &descriptions, actor, "drops", item);
+
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::DROP,
+    }));
+
+    Status::Continue
+}
+
+pub fn equip(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+    player: Query<(Entity, Option<&MeleeSlot>, Option<&GunSlot>), With<Player>>,
+    items: Query<(
+        Entity,
+        &Item,
+        Option<&Equipped>,
+        Option<&MeleeWeapon>,
+        Option<&RangedWeapon>,
+    )>,
+    descriptions: Query<&Description>,
+) -> Status {
+    let id = match action.as_ref() {
+        Action::Equip(EquipAction::Intent { item }) => *item,
+        _ => return Status::Continue,
+    };
+
+    let (actor, melee_slot, gun_slot) = player.single();
+
+    let (item, equipped, slot) =
+        match items
+            .iter()
+            .find_map(|(entity, item, equipped, melee, ranged)| {
+                (item.owner == Some(actor) && entity.id() == id).then_some((
+                    entity,
+                    equipped.is_some(),
+                    Slot::of(melee, ranged),
+                ))
+            }) {
+            Some(it) => it,
+            None => {
+                let action = Action::Log("You don't carry that".to_string());
+                return Status::Reject(vec![action]);
+            }
+        };
+
+    let description = descriptions.get(item).unwrap();
+
+    if equipped {
+        let action = Action::Log(format!(
+            "{} is already equipped",
+            description.to_capitalized_string()
+        ));
+        return Status::Reject(vec![action]);
+    }
+
+    let slot = match slot {
+        Some(Slot::Melee) if melee_slot.is_some() => Slot::Melee,
+        Some(Slot::Gun) if gun_slot.is_some() => Slot::Gun,
+        _ => {
+            let action = Action::Log(format!(
+                "{} can't be equipped",
+                description.to_capitalized_string()
+            ));
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    // Only one weapon per slot, so swap out the one equipped already.
+    if let Some(other) = items
+        .iter()
+        .find_map(|(entity, item, equipped, melee, ranged)| {
+            (item.owner == Some(actor)
+                && equipped.is_some()
+                && Slot::of(melee, ranged) == Some(slot))
+            .then_some(entity)
+        })
+    {
+        reactions
+            .0
+            .push(Action::Unequip(UnequipAction::Stow { actor, item: other }));
+        log(&mut reactions, &descriptions, actor, "unequips", other);
+    }
+
+    reactions
+        .0
+        .push(Action::Equip(EquipAction::Wield { actor, item }));
+    log(&mut reactions, &descriptions, actor, "equips", item);
+
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::EQUIP,
+    }));
+
+    Status::Continue
+}
+
+pub fn unequip(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+    player: Query<Entity, With<Player>>,
+    items: Query<(Entity, &Item, Option<&Equipped>)>,
+    descriptions: Query<&Description>,
+) -> Status {
+    let id = match action.as_ref() {
+        Action::Unequip(UnequipAction::Intent { item }) => *item,
+        _ => return Status::Continue,
+    };
+
+    let actor = player.single();
+
+    let (item, equipped) = match items.iter().find_map(|(entity, item, equipped)| {
+        (item.owner == Some(actor) && entity.id() == id).then_some((entity, equipped.is_some()))
+    }) {
+        Some(it) => it,
+        None => {
+            let action = Action::Log("You don't carry that".to_string());
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    if !equipped {
+        let description = descriptions.get(item).unwrap();
+        let action = Action::Log(format!(
+            "{} isn't equipped",
+            description.to_capitalized_string()
+        ));
+        return Status::Reject(vec![action]);
+    }
+
+    reactions
+        .0
+        .push(Action::Unequip(UnequipAction::Stow { actor, item }));
+    log(&mut reactions, &descriptions, actor, "unequips", item);
+
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::UNEQUIP,
+    }));
+
+    Status::Continue
+}
+
+/// Logs `actor` doing `verb` with `item`, e.g. "the Hacker drops a lead pipe".
+fn log(
+    reactions: &mut Reactions,
+    descriptions: &Query<&Description>,
+    actor: Entity,
+    verb: &str,
+    item: Entity,
+) {
+    if let (Ok(actor), Ok(item)) = (descriptions.get(actor), descriptions.get(item)) {
+        reactions.0.push(Action::Log(format!(
+            "{} {} {}",
+            actor.to_capitalized_string(),
+            verb,
+            item
+        )));
+    }
+}
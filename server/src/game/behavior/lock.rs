@@ -19,8 +19,16 @@ pub fn door(
     {
         let mut actions = Vec::new();
 
-        if player.contains(actor) && let Ok(desc) = descriptions.get(target) {
-            actions.push(Action::Log(format!("{} is locked and can't be opened directly", desc)));
+        if !player.contains(actor) {
+            actions.push(Action::EndTurn(EndTurnAction {
+                actor,
+                cost: cost::WAIT,
+            }));
+        } else if let Ok(desc) = descriptions.get(target) {
+            actions.push(Action::Log(format!(
+                "{} is locked and can't be opened directly",
+                desc
+            )));
         };
 
         Status::Reject(actions)
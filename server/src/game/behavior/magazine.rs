@@ -0,0 +1,178 @@
+use std::cmp::Reverse;
+
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, *};
+
+pub fn reload(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+    player: Query<Entity, With<Player>>,
+    weapons: Query<(Entity, &Item, &RangedWeapon), With<Equipped>>,
+    magazines: Query<(Entity, &Item, &Magazine)>,
+    descriptions: Query<&Description>,
+) -> Status {
+    match action.as_ref() {
+        Action::Reload(ReloadAction::Intent) => {}
+        _ => return Status::Continue,
+    };
+
+    let actor = player.single();
+
+    let (weapon, gun) = match equipped(actor, &weapons) {
+        Some(it) => it,
+        None => {
+            let action = Action::Log("You have no projectile weapon equipped".to_string());
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    let description = descriptions.get(weapon).unwrap();
+
+    let attached = magazines.iter().find_map(|(entity, _, magazine)| {
+        (magazine.attached == Some(weapon)).then_some((entity, magazine))
+    });
+    let loaded = attached.map_or(0, |(_, magazine)| magazine.amount);
+
+    let spares: Vec<_> = magazines
+        .iter()
+        .filter(|(_, item, magazine)| {
+            item.owner == Some(actor) && magazine.attached.is_none() && magazine.amount > 0
+        })
+        .collect();
+
+    if spares.is_empty() {
+        let action = Action::Log("You have no spare magazines".to_string());
+        return Status::Reject(vec![action]);
+    }
+
+    let magazine = match spares
+        .into_iter()
+        .filter(|(_, _, magazine)| magazine.gun == gun.kind)
+        .min_by_key(|(entity, _, magazine)| (Reverse(magazine.amount), entity.id()))
+    {
+        Some((entity, _, magazine)) if magazine.amount > loaded => entity,
+        Some(_) => {
+            let action = Action::Log(format!(
+                "You have no magazine with more rounds than the one in {}",
+                description
+            ));
+            return Status::Reject(vec![action]);
+        }
+        None => {
+            let action = Action::Log(format!("None of your magazines fit {}", description));
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    if let Some((attached, _)) = attached {
+        reactions
+            .0
+            .push(Action::EjectMagazine(EjectMagazineAction::Detach {
+                actor,
+                weapon,
+                magazine: attached,
+            }));
+    }
+    reactions.0.push(Action::Reload(ReloadAction::Attach {
+        actor,
+        weapon,
+        magazine,
+    }));
+
+    if let (Ok(actor), Ok(magazine)) = (descriptions.get(actor), descriptions.get(magazine)) {
+        reactions.0.push(Action::Log(format!(
+            "{} reloads {} with {}",
+            actor.to_capitalized_string(),
+            description,
+            magazine
+        )));
+    }
+
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::RELOAD,
+    }));
+
+    Status::Continue
+}
+
+pub fn eject(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+    player: Query<Entity, With<Player>>,
+    weapons: Query<(Entity, &Item, &RangedWeapon), With<Equipped>>,
+    magazines: Query<(Entity, &Magazine)>,
+    descriptions: Query<&Description>,
+) -> Status {
+    match action.as_ref() {
+        Action::EjectMagazine(EjectMagazineAction::Intent) => {}
+        _ => return Status::Continue,
+    };
+
+    let actor = player.single();
+
+    let weapon = match equipped(actor, &weapons) {
+        Some((weapon, _)) => weapon,
+        None => {
+            let action = Action::Log("You have no projectile weapon equipped".to_string());
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    let description = descriptions.get(weapon).unwrap();
+
+    let magazine = match magazines
+        .iter()
+        .find_map(|(entity, magazine)| (magazine.attached == Some(weapon)).then_some(entity))
+    {
+        Some(it) => it,
+        None => {
+            let action = Action::Log(format!(
+                "{} has no magazine attached",
+                description.to_capitalized_string()
+            ));
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    reactions
+        .0
+        .push(Action::EjectMagazine(EjectMagazineAction::Detach {
+            actor,
+            weapon,
+            magazine,
+        }));
+
+    if let (Ok(actor), Ok(magazine)) = (descriptions.get(actor), descriptions.get(magazine)) {
+        reactions.0.push(Action::Log(format!(
+            "{} ejects {} from {}",
+            actor.to_capitalized_string(),
+            magazine,
+            description
+        )));
+    }
+
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::EJECT,
+    }));
+
+    Status::Continue
+}
+
+/// The projectile weapon `actor` has equipped.
+fn equipped(
+    actor: Entity,
+    weapons: &Query<(Entity, &Item, &RangedWeapon), With<Equipped>>,
+) -> Option<(Entity, ProjectileGun)> {
+    weapons.iter().find_map(|(entity, item, weapon)| {
+        if item.owner != Some(actor) {
+            return None;
+        }
+
+        weapon.projectile().map(|gun| (entity, gun))
+    })
+}
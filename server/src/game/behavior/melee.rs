@@ -27,7 +27,10 @@ pub fn intent(
             });
             reactions.0.push(action);
 
-            followups.0.push(Action::EndTurn(actor));
+            followups.0.push(Action::EndTurn(EndTurnAction {
+                actor,
+                cost: cost::MELEE,
+            }));
 
             Status::Continue
         }
@@ -79,6 +82,12 @@ pub fn attack(
 
     let damage = weapons.get(weapon).unwrap().damage;
 
+    reactions.0.push(Action::Noise(NoiseAction {
+        actor,
+        source: *position,
+        loudness: noise::MELEE,
+    }));
+
     // TODO hit chance etc
 
     for target in targets {
@@ -6,16 +6,21 @@ mod door;
 mod dwim;
 mod gateway;
 mod god_mode;
+mod hearing;
+mod inventory;
 mod lock;
+mod magazine;
 mod melee;
 mod memorize;
 mod r#move;
+mod power;
 mod radial_lines;
 mod room;
 mod shoot;
 mod spot;
 mod state;
 mod switch;
+mod turn;
 mod view;
 
 pub use ai::behavior as ai;
@@ -29,20 +34,31 @@ pub use dwim::r#move as dwim_move;
 pub use dwim::shoot as dwim_shoot;
 pub use gateway::behavior as gateway;
 pub use god_mode::behavior as god_mode;
+pub use hearing::behavior as hearing;
+pub use inventory::drop as drop_item;
+pub use inventory::equip;
+pub use inventory::pick_up;
+pub use inventory::unequip;
 pub use lock::close as lock_close;
 pub use lock::door as lock_door;
+pub use magazine::eject as eject_magazine;
+pub use magazine::reload;
 pub use melee::attack as melee_attack;
 pub use melee::intent as melee_intent;
 pub use memorize::ai as ai_memorize;
 pub use memorize::behavior as memorize;
+pub use power::behavior as power;
 pub use r#move::behavior as r#move;
 pub use room::behavior as room;
+pub use shoot::dispatch_beam;
 pub use shoot::dispatch_projectile;
 pub use shoot::intent as shoot_intent;
+pub use shoot::shoot_energy;
 pub use shoot::shoot_projectile;
 pub use spot::behavior as spot;
 pub use state::behavior as state;
 pub use switch::behavior as switch;
+pub use turn::behavior as turn;
 pub use view::behavior as view;
 pub use view::view_all;
 
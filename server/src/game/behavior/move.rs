@@ -41,14 +41,21 @@ pub fn behavior(
 
         let mut actions = Vec::new();
 
-        player.contains(actor).then(|| {
+        if player.contains(actor) {
             let object = match desc {
                 Some(it) => it.to_string(),
                 None => "something".to_string(),
             };
 
             actions.push(Action::Log(format!("You run into {}", object)));
-        });
+        } else {
+            // NPCs don't get to try again, or they would never hand over the
+            // turn
+            actions.push(Action::EndTurn(EndTurnAction {
+                actor,
+                cost: cost::WAIT,
+            }));
+        }
 
         return Status::Reject(actions);
     };
@@ -58,7 +65,10 @@ pub fn behavior(
         delta,
         position: target,
     }));
-    followups.0.push(Action::EndTurn(actor));
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::MOVE,
+    }));
 
     Status::Continue
 }
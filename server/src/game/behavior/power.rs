@@ -0,0 +1,59 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, *};
+
+pub fn behavior(
+    action: Res<Action>,
+    mut reactions: ResMut<Reactions>,
+    player: Query<Entity, With<Player>>,
+    weapons: Query<(Entity, &Item, &RangedWeapon, Option<&Equipped>)>,
+    descriptions: Query<&Description>,
+) -> Status {
+    let power = match action.as_ref() {
+        Action::Power(PowerAction::Intent { power }) => *power,
+        _ => return Status::Continue,
+    };
+
+    let player = player.single();
+
+    // Prefer the equipped gun, but a carried one can be set up in advance.
+    let (weapon, gun) = match weapons
+        .iter()
+        .filter_map(|(entity, item, weapon, equipped)| {
+            if item.owner != Some(player) {
+                return None;
+            }
+
+            weapon.energy().map(|gun| (equipped.is_none(), entity, gun))
+        })
+        .min_by_key(|(stowed, entity, _)| (*stowed, *entity))
+    {
+        Some((_, entity, gun)) => (entity, gun),
+        None => {
+            let action = Action::Log("You carry no energy weapon".to_string());
+            return Status::Reject(vec![action]);
+        }
+    };
+
+    let description = descriptions.get(weapon).unwrap();
+
+    if power == 0 || power > gun.max {
+        let action = Action::Log(format!(
+            "{} can only be set to a power level between 1 and {}",
+            description.to_capitalized_string(),
+            gun.max
+        ));
+        return Status::Reject(vec![action]);
+    }
+
+    reactions
+        .0
+        .push(Action::Power(PowerAction::Set { weapon, power }));
+    reactions.0.push(Action::Log(format!(
+        "{} is set to power level {}",
+        description.to_capitalized_string(),
+        power
+    )));
+
+    Status::Continue
+}
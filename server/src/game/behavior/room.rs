@@ -3,9 +3,11 @@ use std::convert::TryInto;
 use std::ops::Not;
 
 use bevy_ecs::prelude::*;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
 
 use crate::game::resource::*;
-use crate::game::room::{Room, Rooms};
+use crate::game::room::{Layout, Room, Rooms};
 use crate::game::{component::*, *};
 
 pub fn behavior(
@@ -14,10 +16,15 @@ pub fn behavior(
     room_index: Res<RoomId>,
     spawners: Query<(), With<RoomSpawner>>,
     positions: Query<&Position>,
+    player: Query<(), With<Player>>,
+    layout: Res<Layout>,
+    mut rng: ResMut<Random>,
     // bulkhead_doors: Query<&Children, With<Door>>,
 ) -> Status {
-    let target = match action.as_ref() {
-        Action::OpenDoor(OpenDoorAction { target, .. }) if spawners.contains(*target) => target,
+    let (actor, target) = match action.as_ref() {
+        Action::OpenDoor(OpenDoorAction { actor, target }) if spawners.contains(*target) => {
+            (actor, target)
+        }
         _ => return Status::Continue,
     };
 
@@ -54,18 +61,47 @@ pub fn behavior(
         |room: &Room| !room.is_dead_end()
     };
 
-    let mut rng = thread_rng();
-    let mut room = match rooms.random(&mut rng, predicate) {
+    let origin = layout.origin(&position.room).unwrap();
+    let gateway = origin + position.coordinates;
+
+    // Try every room, attached by each of its spawners and mirrored or not,
+    // until one fits
+    let candidate = rooms
+        .candidates(&mut *rng, predicate)
+        .into_iter()
+        .find_map(|room| {
+            let mut variants: Vec<_> = room
+                .spawners(&mut *rng)
+                .into_iter()
+                .cartesian_product([false, true])
+                .collect();
+            variants.shuffle(&mut *rng);
+
+            variants.into_iter().find_map(|(spawner, mirror)| {
+                let mut room = room.clone();
+                room.turn_towards(&spawner, &direction, mirror).unwrap();
+
+                let origin = gateway - *room.position_of(&spawner).unwrap();
+                layout
+                    .fits(&room, origin, gateway)
+                    .then_some((room, spawner, origin))
+            })
+        });
+
+    let (mut room, spawner, origin) = match candidate {
         Some(it) => it,
         None => {
-            log::error!("Could not find a room fulfilling the predicate");
-            return Status::Reject(vec![]);
+            log::warn!("Could not find a room fitting at {}", gateway);
+
+            let mut actions = Vec::new();
+            if player.contains(*actor) {
+                actions.push(Action::Log("The door is stuck".to_string()));
+            }
+
+            return Status::Reject(actions);
         }
     };
 
-    let spawner = room.random_spawner(&mut rng).unwrap().to_owned();
-
-    room.turn_towards(&spawner, &direction).unwrap();
     room.erase_player();
     room.release_spawner(&spawner);
 
@@ -77,6 +113,7 @@ pub fn behavior(
         target: *target,
         id,
         room,
+        origin,
     });
 
     let gateway = Action::SpawnGateway(GatewaySpawnAction {
@@ -90,3 +127,154 @@ pub fn behavior(
 
     Status::Reject(vec![spawn, gateway, action.to_owned()])
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use glam::ivec2;
+
+    use super::*;
+
+    /// Has the player open the spawner at `coordinates` of the start room.
+    fn open(game: &mut Game, coordinates: IVec2) {
+        let player = game
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .iter(&game.world)
+            .next()
+            .unwrap();
+        let spawner = game
+            .world
+            .query_filtered::<(Entity, &Position), (With<RoomSpawner>, With<Door>)>()
+            .iter(&game.world)
+            .find_map(|(entity, pos)| (pos.coordinates == coordinates).then_some(entity))
+            .unwrap();
+
+        game.input(Action::OpenDoor(OpenDoorAction {
+            actor: player,
+            target: spawner,
+        }));
+    }
+
+    /// Asserts that `room` was spawned and only shares walls and the gateway
+    /// at `gateway` with the start room.
+    fn assert_placed(game: &mut Game, room: RoomId, gateway: IVec2) {
+        let layout = game.world.resource::<Layout>().clone();
+        assert!(layout.origin(&room).is_some());
+
+        // Every tile has a floor, walls have a wall on top
+        let mut cells: HashMap<(RoomId, IVec2), bool> = HashMap::new();
+        for (pos, wall) in game
+            .world
+            .query_filtered::<(&Position, Option<&Wall>), Or<(With<Floor>, With<Wall>)>>()
+            .iter(&game.world)
+        {
+            let cell = layout.origin(&pos.room).unwrap() + pos.coordinates;
+            *cells.entry((pos.room, cell)).or_default() |= wall.is_some();
+        }
+
+        for ((id, cell), wall) in cells.iter() {
+            if *id != RoomId(0) {
+                continue;
+            }
+
+            if let Some(other) = cells.get(&(room, *cell)) {
+                assert!(
+                    *cell == gateway || (*wall && *other),
+                    "Rooms overlap at {}",
+                    cell
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_placement() {
+        let mut game = Game::with_rooms(
+            "
+  ######
+#-#··###
+#······#
+#·····@#
+#o#··###
+#·######
+###",
+            &["
+#########
+#··#····#
+#·······#
+#·····#·#
+#·#·#·#-#
+#·###·#
+#··#··#
+#····##
+|··#·##
+#··#··##
+#####··#
+   #####"],
+        );
+
+        open(&mut game, ivec2(1, 5));
+        assert_placed(&mut game, RoomId(1), ivec2(1, 5));
+    }
+
+    #[test]
+    fn test_placement_offset() {
+        let mut game = Game::with_rooms(
+            "
+#########
+#··#····#
+#·······#
+#·····#·#
+#·#·#·#-#
+#·###·#
+#··#··#
+#····##
+|@·#·##
+#··#··##
+#####··#
+   #####",
+            &["
+  ######
+  #··###
+#-#···##
+#······#
+#·····@#
+#o#··###
+#·######
+###"],
+        );
+
+        open(&mut game, ivec2(7, 7));
+        assert_placed(&mut game, RoomId(1), ivec2(7, 7));
+    }
+
+    #[test]
+    fn test_placement_rejected() {
+        // The second spawner faces a hole too small for any room
+        let mut game = Game::with_rooms(
+            "
+#######
+#·····|
+#·#-#·#
+#·# #·#
+#·###·#
+#@····#
+#######",
+            &["
+#####
+|···|
+#####"],
+        );
+
+        open(&mut game, ivec2(6, 5));
+        assert_placed(&mut game, RoomId(1), ivec2(6, 5));
+
+        open(&mut game, ivec2(3, 4));
+        let layout = game.world.resource::<Layout>();
+        assert!(layout.origin(&RoomId(2)).is_none());
+        let log = game.world.resource::<Log>();
+        assert_eq!(log.read().last().unwrap(), "The door is stuck");
+    }
+}
@@ -1,6 +1,16 @@
+use std::collections::HashMap;
+
 use bevy_ecs::prelude::*;
 
-use crate::game::{component::*, Status, *};
+use crate::game::{
+    component::*,
+    pathfinding::{bresenham, euclidian_distance},
+    resource::Random,
+    Status, *,
+};
+
+/// How far shots travel, which is as far as anyone can see.
+const RANGE: f32 = 10.0;
 
 pub fn intent(
     action: Res<Action>,
@@ -27,7 +37,13 @@ pub fn intent(
                         weapon: entity,
                     }));
                 }
-                RangedWeapon::Energy(_) => todo!(),
+                RangedWeapon::Energy(_) => {
+                    reactions.0.push(Action::Shoot(ShootAction::EnergyGun {
+                        actor: *actor,
+                        target: *target,
+                        weapon: entity,
+                    }));
+                }
             }
             Status::Continue
         }
@@ -48,6 +64,7 @@ pub fn shoot_projectile(
     descriptions: Query<&Description>,
     weapons: Query<&RangedWeapon>,
     magazines: Query<(Entity, &Magazine)>,
+    positions: Query<&Position>,
     mut reactions: ResMut<Reactions>,
     mut followups: ResMut<FollowUps>,
 ) -> Status {
@@ -78,13 +95,25 @@ pub fn shoot_projectile(
                     target: *target,
                     weapon: *weapon,
                     magazine: entity,
+                    impact: None,
                 });
 
                 for _ in 0..amount {
                     reactions.0.push(action.clone());
                 }
 
-                followups.0.push(Action::EndTurn(*actor));
+                if let Ok(position) = positions.get(*actor) {
+                    reactions.0.push(Action::Noise(NoiseAction {
+                        actor: *actor,
+                        source: *position,
+                        loudness: noise::GUNFIRE,
+                    }));
+                }
+
+                followups.0.push(Action::EndTurn(EndTurnAction {
+                    actor: *actor,
+                    cost: cost::shoot(amount),
+                }));
 
                 Status::Continue
             } else {
@@ -110,32 +139,333 @@ pub fn shoot_projectile(
 }
 
 pub fn dispatch_projectile(
-    action: Res<Action>,
+    mut action: ResMut<Action>,
     magazines: Query<&Magazine>,
+    sights: Query<&Sight>,
+    solids: Query<(), With<Solid>>,
+    vulnerables: Query<&Vulnerable>,
+    descriptions: Query<&Description>,
+    mut rng: ResMut<Random>,
     mut reactions: ResMut<Reactions>,
 ) -> Status {
-    let (actor, target, weapon, magazine) = match action.as_ref() {
+    let (actor, target, weapon, magazine, impact) = match action.as_mut() {
         Action::Shoot(ShootAction::DispatchProjectile {
             actor,
             target,
             weapon,
             magazine,
-        }) => (actor, target, weapon, magazine),
+            impact,
+        }) => (*actor, *target, *weapon, *magazine, impact),
         _ => return Status::Continue,
     };
 
-    // TODO hit chance, use position instead of target etc
+    let damage = magazines.get(magazine).unwrap().projectile.damage;
 
-    let magazine = magazines.get(*magazine).unwrap();
+    let shot = trace(
+        actor,
+        target,
+        damage.offense,
+        &sights,
+        &solids,
+        &vulnerables,
+        &mut rng,
+    );
+    *impact = shot.as_ref().map(|it| it.cell);
 
-    let action = Action::Hit(HitAction {
-        actor: *actor,
-        target: *target,
-        direction: HitDirection::None,
-        weapon: *weapon,
-        damage: magazine.projectile.damage,
-    });
-    reactions.0.push(action);
+    hit(
+        actor,
+        target,
+        weapon,
+        damage,
+        shot,
+        &descriptions,
+        &mut reactions,
+    );
 
     Status::Continue
 }
+
+pub fn shoot_energy(
+    action: Res<Action>,
+    descriptions: Query<&Description>,
+    weapons: Query<(&RangedWeapon, Option<&PowerSetting>)>,
+    batteries: Query<&PersonalBattery>,
+    positions: Query<&Position>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+) -> Status {
+    let (actor, target, weapon) = match action.as_ref() {
+        Action::Shoot(ShootAction::EnergyGun {
+            actor,
+            target,
+            weapon,
+        }) => (actor, target, weapon),
+        _ => return Status::Continue,
+    };
+
+    let (gun, setting) = weapons
+        .get(*weapon)
+        .ok()
+        .and_then(|(w, setting)| w.energy().map(|gun| (gun, setting)))
+        .unwrap();
+    let power = setting.map(|it| it.0).unwrap_or(gun.max);
+
+    match batteries.get(*actor) {
+        Ok(battery) if battery.charge >= gun.cost(power) => {
+            reactions.0.push(Action::Shoot(ShootAction::DispatchBeam {
+                actor: *actor,
+                target: *target,
+                weapon: *weapon,
+                power,
+                impact: None,
+            }));
+
+            if let Ok(position) = positions.get(*actor) {
+                reactions.0.push(Action::Noise(NoiseAction {
+                    actor: *actor,
+                    source: *position,
+                    loudness: noise::BEAM,
+                }));
+            }
+
+            followups.0.push(Action::EndTurn(EndTurnAction {
+                actor: *actor,
+                cost: cost::BEAM,
+            }));
+
+            Status::Continue
+        }
+        _ => {
+            let mut actions = Vec::new();
+
+            if let (Ok(actor), Ok(weapon)) = (descriptions.get(*actor), descriptions.get(*weapon)) {
+                actions.push(Action::Log(format!(
+                    "{} doesn't have enough battery charge to fire {}",
+                    actor.to_capitalized_string(),
+                    weapon
+                )));
+            };
+
+            Status::Reject(actions)
+        }
+    }
+}
+
+pub fn dispatch_beam(
+    mut action: ResMut<Action>,
+    weapons: Query<&RangedWeapon>,
+    sights: Query<&Sight>,
+    solids: Query<(), With<Solid>>,
+    vulnerables: Query<&Vulnerable>,
+    descriptions: Query<&Description>,
+    mut rng: ResMut<Random>,
+    mut reactions: ResMut<Reactions>,
+) -> Status {
+    let (actor, target, weapon, power, impact) = match action.as_mut() {
+        Action::Shoot(ShootAction::DispatchBeam {
+            actor,
+            target,
+            weapon,
+            power,
+            impact,
+        }) => (*actor, *target, *weapon, *power, impact),
+        _ => return Status::Continue,
+    };
+
+    let gun = weapons.get(weapon).ok().and_then(|w| w.energy()).unwrap();
+    let damage = gun.damage(power);
+
+    let shot = trace(
+        actor,
+        target,
+        damage.offense,
+        &sights,
+        &solids,
+        &vulnerables,
+        &mut rng,
+    );
+    *impact = shot.as_ref().map(|it| it.cell);
+
+    hit(
+        actor,
+        target,
+        weapon,
+        damage,
+        shot,
+        &descriptions,
+        &mut reactions,
+    );
+
+    Status::Continue
+}
+
+/// Where a shot ends up, relative to the shooter.
+struct Impact {
+    cell: IVec2,
+    entity: Option<Entity>,
+}
+
+/// Chance of a shot hitting its target, rather than missing it.
+fn hit_chance(offense: u8, defense: u8, distance: f32) -> f64 {
+    let chance = 0.5 + 0.1 * (offense as f64 - defense as f64) - 0.03 * distance as f64;
+    chance.clamp(0.05, 0.95)
+}
+
+/// Traces a shot from `actor` at `target` on the plane seen by the actor, so
+/// shots pass through gateways just like sight does.
+///
+/// The shot travels along a Bresenham line through the target's position and
+/// stops at the first solid entity in its way. The target itself is only hit
+/// with a chance depending on `offense`, its defense and the distance; when
+/// missed, the shot continues behind it, hitting whatever comes next.
+fn trace(
+    actor: Entity,
+    target: Entity,
+    offense: u8,
+    sights: &Query<&Sight>,
+    solids: &Query<(), With<Solid>>,
+    vulnerables: &Query<&Vulnerable>,
+    rng: &mut Random,
+) -> Option<Impact> {
+    let sight = sights.get(actor).ok()?;
+
+    let goal = sight.position_of(&target)?;
+
+    let mut obstacles: HashMap<IVec2, Vec<Entity>> = HashMap::new();
+    for (entity, cells) in sight.seeing.iter() {
+        if *entity != actor && (*entity == target || solids.contains(*entity)) {
+            for cell in cells {
+                obstacles.entry(*cell).or_default().push(*entity);
+            }
+        }
+    }
+
+    let origin = ivec2(0, 0);
+    let defense = vulnerables.get(target).map(|v| v.defense).unwrap_or(0);
+    let chance = hit_chance(offense, defense, euclidian_distance(&origin, &goal));
+
+    let mut last = origin;
+
+    for cell in
+        bresenham(origin, goal).take_while(|cell| euclidian_distance(&origin, cell) <= RANGE)
+    {
+        last = cell;
+
+        let entities = match obstacles.get(&cell) {
+            Some(it) => it,
+            None => continue,
+        };
+
+        if entities.contains(&target) {
+            if rng.gen_bool(chance) {
+                return Some(Impact {
+                    cell,
+                    entity: Some(target),
+                });
+            }
+        } else if let Some(entity) = entities.iter().min() {
+            return Some(Impact {
+                cell,
+                entity: Some(*entity),
+            });
+        }
+    }
+
+    Some(Impact {
+        cell: last,
+        entity: None,
+    })
+}
+
+/// Reacts to `shot` with a hit on whatever it struck.
+fn hit(
+    actor: Entity,
+    target: Entity,
+    weapon: Entity,
+    damage: Damage,
+    shot: Option<Impact>,
+    descriptions: &Query<&Description>,
+    reactions: &mut Reactions,
+) {
+    let entity = shot.and_then(|it| it.entity);
+
+    if entity != Some(target)
+        && let (Ok(actor), Ok(target)) = (descriptions.get(actor), descriptions.get(target))
+    {
+        reactions.0.push(Action::Log(format!(
+            "{} misses {}",
+            actor.to_capitalized_string(),
+            target
+        )));
+    }
+
+    if let Some(entity) = entity {
+        reactions.0.push(Action::Hit(HitAction {
+            actor,
+            target: entity,
+            direction: HitDirection::None,
+            weapon,
+            damage,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::ivec2;
+
+    use super::*;
+    use crate::game::resource::Log;
+
+    #[test]
+    fn test_energy_gun_battery() {
+        let mut game = Game::setup(0).start(
+            "
+#####
+#@··#
+#####"
+                .parse()
+                .unwrap(),
+        );
+
+        let player = game
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .single(&game.world);
+        let rifle = game
+            .world
+            .query::<(Entity, &RangedWeapon)>()
+            .iter(&game.world)
+            .find_map(|(entity, weapon)| weapon.energy().map(|_| entity))
+            .unwrap();
+        let wall = game
+            .world
+            .query_filtered::<(Entity, &Position), With<Wall>>()
+            .iter(&game.world)
+            .find_map(|(entity, pos)| (pos.coordinates == ivec2(4, 1)).then_some(entity))
+            .unwrap();
+
+        game.input(Action::Equip(EquipAction::Intent { item: rifle.id() }));
+
+        let shoot = Action::Shoot(ShootAction::Intent {
+            actor: player,
+            target: wall,
+        });
+        let charge = |game: &mut Game| game.world.get::<PersonalBattery>(player).unwrap().charge;
+
+        // 50 charge covers two shots at the default power of 5, costing 18
+        assert_eq!(charge(&mut game), 50);
+        game.input(shoot.clone());
+        assert_eq!(charge(&mut game), 32);
+        game.input(shoot.clone());
+        assert_eq!(charge(&mut game), 14);
+
+        game.input(shoot);
+        assert_eq!(charge(&mut game), 14);
+        let log = game.world.resource::<Log>();
+        assert_eq!(
+            log.read().last().unwrap(),
+            "The Hacker doesn't have enough battery charge to fire an RW-45 Ion Rifle"
+        );
+    }
+}
@@ -3,12 +3,19 @@ use std::time::Instant;
 use bevy_ecs::prelude::*;
 
 use crate::game::component::*;
+use crate::game::pathfinding::RoomGraph;
 use crate::game::*;
 
 pub fn behavior(
     action: Res<Action>,
-    player: Query<(&Position, &Sight, &Memory), With<Player>>,
+    player: Query<(Entity, &Position, &Sight, &Memory), With<Player>>,
     entities: Query<(&Renderable, Option<&Door>, Option<&Wall>)>,
+    gateways: Query<(&Position, &Gateway)>,
+    positions: Query<&Position>,
+    items: Query<(Entity, &Item, &Description, Option<&Equipped>)>,
+    status: Query<(&Vulnerable, Option<&PersonalBattery>, Option<&God>)>,
+    ranged: Query<&RangedWeapon>,
+    magazines: Query<(&Item, &Magazine)>,
     mut reactions: ResMut<Reactions>,
 ) -> Status {
     match action.as_ref() {
@@ -24,14 +31,19 @@ pub fn behavior(
 
     let now = Instant::now();
 
-    let (position, sight, memory) = player.single();
+    let (player, position, sight, memory) = player.single();
 
     let view = sight.seeing.iter().filter_map(|(e, pos)| {
         entities.get(*e).ok().map(|(renderable, door, wall)| {
             (
                 e.id(),
                 api::Components {
-                    positions: pos.iter().cloned().map_into().collect(),
+                    positions: pos
+                        .iter()
+                        .map(|pos| *pos + position.coordinates)
+                        .sorted_by_key(|pos| (pos.x, pos.y))
+                        .map_into()
+                        .collect(),
                     renderable: Some(renderable.into()),
                     door: door.map(|it| it.into()),
                     wall: wall.map(|it| it.into()),
@@ -41,28 +53,77 @@ pub fn behavior(
         })
     });
 
+    // Memories of other rooms are projected into the player's room, the way
+    // they'd be seen through the gateways leading there.
+    let offsets = RoomGraph::new(gateways.iter().map(|(pos, gateway)| {
+        let twin = positions.get(gateway.twin).unwrap();
+        (*pos, *twin, gateway.direction)
+    }))
+    .offsets(position.room);
+
     let entities = memory
         .0
         .iter()
-        .filter(|(_, cs)| cs.position.room == position.room)
-        .map(|(e, cs)| {
-            let pos = cs.position.coordinates - position.coordinates;
+        .filter_map(|(e, cs)| {
+            let offset = offsets.get(&cs.position.room)?;
+            Some((e, cs, cs.position.coordinates + *offset))
+        })
+        .map(|(e, cs, pos)| {
             (
                 e.id(),
                 api::Components {
-                    positions: vec![api::PositionComponent { x: pos.x, y: pos.y }],
-                    renderable: Some((&cs.renderable).into()),
-                    door: cs.door.as_ref().map(|it| it.into()),
-                    wall: cs.wall.as_ref().map(|it| it.into()),
-                    memory: Some(api::MemoryComponent {}),
+                    positions: vec![pos.into()],
+                    ..cs.into()
                 },
             )
         })
         .chain(view)
         .collect();
 
+    let inventory = items
+        .iter()
+        .filter(|(_, item, ..)| item.owner == Some(player))
+        .sorted_by_key(|(entity, ..)| entity.id())
+        .map(|(entity, _, description, equipped)| api::InventoryItem {
+            entity: entity.id(),
+            name: description.to_string(),
+            equipped: equipped.is_some(),
+        })
+        .collect();
+
+    let equipped: Vec<_> = items
+        .iter()
+        .filter(|(_, item, _, equipped)| item.owner == Some(player) && equipped.is_some())
+        .sorted_by_key(|(entity, ..)| entity.id())
+        .map(|(entity, _, description, _)| (entity, description))
+        .collect();
+
+    let status = status
+        .get(player)
+        .ok()
+        .map(|(vulnerable, battery, god)| api::PlayerStatus {
+            hp: vulnerable.hp as u32,
+            max_hp: vulnerable.max as u32,
+            charge: battery.map_or(0, |it| it.charge as u32),
+            max_charge: battery.map_or(0, |it| it.max as u32),
+            god: god.is_some(),
+            weapons: equipped
+                .iter()
+                .map(|(_, description)| description.to_string())
+                .collect(),
+            ammo: equipped.iter().find_map(|(entity, _)| {
+                let gun = ranged.get(*entity).ok()?.projectile()?;
+                Some(ammo(player, *entity, gun, &magazines))
+            }),
+        });
+
     reactions.0.push(Action::State(StateAction::Update {
-        state: api::State { entities },
+        state: api::State {
+            entities,
+            inventory,
+            status,
+            version: 0,
+        },
     }));
 
     let duration = Instant::now() - now;
@@ -70,3 +131,26 @@ pub fn behavior(
 
     Status::Continue
 }
+
+/// Rounds for `weapon`, a `gun` equipped by `player`.
+fn ammo(
+    player: Entity,
+    weapon: Entity,
+    gun: ProjectileGun,
+    magazines: &Query<(&Item, &Magazine)>,
+) -> api::Ammo {
+    let mut ammo = api::Ammo::default();
+
+    for (item, magazine) in magazines.iter() {
+        if magazine.attached == Some(weapon) {
+            ammo.loaded += magazine.amount as u32;
+        } else if magazine.attached.is_none()
+            && item.owner == Some(player)
+            && magazine.gun == gun.kind
+        {
+            ammo.spare += magazine.amount as u32;
+        }
+    }
+
+    ammo
+}
@@ -49,7 +49,10 @@ pub fn behavior(
         }
     }
 
-    followups.0.push(Action::EndTurn(actor));
+    followups.0.push(Action::EndTurn(EndTurnAction {
+        actor,
+        cost: cost::SWITCH,
+    }));
 
     Status::Continue
 }
@@ -0,0 +1,46 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, resource::Scheduler, *};
+
+pub fn behavior(
+    action: Res<Action>,
+    scheduler: Res<Scheduler>,
+    actors: Query<(Entity, &Speed)>,
+    player: Query<(), With<Player>>,
+    mut reactions: ResMut<Reactions>,
+    mut followups: ResMut<FollowUps>,
+) -> Status {
+    match action.as_ref() {
+        Action::EndTurn(EndTurnAction { actor, .. }) => {
+            if scheduler.ends(*actor, player.contains(*actor)) {
+                followups.0.push(Action::Turn(TurnAction::Next));
+            }
+        }
+        Action::Turn(TurnAction::Next) => {
+            // Without the player, nobody is left to wait for.
+            if !actors.iter().any(|(actor, _)| player.contains(actor)) {
+                return Status::Continue;
+            }
+
+            let next = scheduler.next(
+                actors
+                    .iter()
+                    .map(|(actor, speed)| (actor, *speed, player.contains(actor))),
+            );
+
+            if let Some((actor, ticks)) = next {
+                reactions
+                    .0
+                    .push(Action::Turn(TurnAction::Start { actor, ticks }));
+            }
+        }
+        Action::Turn(TurnAction::Start { actor, .. }) => {
+            if !player.contains(*actor) {
+                followups.0.push(Action::AI(*actor));
+            }
+        }
+        _ => {}
+    }
+
+    Status::Continue
+}
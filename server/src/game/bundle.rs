@@ -16,6 +16,7 @@ pub struct Player {
     pub battery: component::PersonalBattery,
     pub vulnerable: component::Vulnerable,
     pub alive: component::Alive,
+    pub speed: component::Speed,
 }
 
 impl Default for Player {
@@ -48,6 +49,7 @@ impl Default for Player {
                 armor: 0,
             },
             alive: component::Alive::Human,
+            speed: Default::default(),
         }
     }
 }
@@ -63,6 +65,7 @@ pub struct NPC {
     pub ai_memory: component::AIMemory,
     pub description: component::Description,
     pub vulnerable: component::Vulnerable,
+    pub speed: component::Speed,
 }
 
 #[derive(Bundle, Default)]
@@ -178,7 +181,6 @@ pub struct MeleeWeapon {
     pub description: component::Description,
 }
 
-#[allow(dead_code)]
 impl MeleeWeapon {
     pub fn lead_pipe() -> Self {
         Self {
@@ -284,12 +286,12 @@ impl Magazine {
 #[derive(Bundle)]
 pub struct EnergyGun {
     pub weapon: component::RangedWeapon,
+    pub power: component::PowerSetting,
     pub item: component::Item,
     pub renderable: component::Renderable,
     pub description: component::Description,
 }
 
-#[allow(dead_code)]
 impl EnergyGun {
     pub fn ion_rifle() -> Self {
         Self {
@@ -305,6 +307,7 @@ impl EnergyGun {
                 efficiency: 3.6,
                 max: 10,
             }),
+            power: component::PowerSetting(5),
             item: Default::default(),
             renderable: component::Renderable::EnergyGun,
             description: component::Description {
@@ -7,13 +7,15 @@ use std::{
 
 use bevy_ecs::prelude::*;
 use glam::IVec2;
+use serde::{Deserialize, Serialize};
 
 use super::room::RoomId;
 
-#[derive(Component, Default, Clone, Debug)]
+#[derive(Component, Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Player;
 
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Article {
     None,
     A,
@@ -38,7 +40,7 @@ impl Display for Article {
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct Description {
     pub name: String,
     pub article: Article,
@@ -61,7 +63,7 @@ impl Description {
     }
 }
 
-#[derive(Component, Default, Clone, Copy, Debug)]
+#[derive(Component, Default, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Wall;
 
 impl From<&Wall> for api::WallComponent {
@@ -70,13 +72,13 @@ impl From<&Wall> for api::WallComponent {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Floor;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct God;
 
-#[derive(Component, Default, Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Component, Default, Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Position {
     pub coordinates: IVec2,
     pub room: RoomId,
@@ -104,14 +106,15 @@ impl Add<IVec2> for Position {
     }
 }
 
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum DoorKind {
     Heavy,
     Bulkhead,
     Storage,
 }
 
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Door {
     pub open: bool,
 }
@@ -128,7 +131,7 @@ impl Default for Door {
     }
 }
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub enum Renderable {
     None,
     Human,
@@ -159,7 +162,7 @@ impl From<&Renderable> for api::RenderableComponent {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum SightKind {
     Blind,
     Eyes,
@@ -178,7 +181,20 @@ pub struct Sight {
     pub mask: HashSet<Position>,
 }
 
-#[derive(Debug, Clone)]
+impl Sight {
+    /// Where `entity` is seen, relative to the viewer. Entities can be seen at
+    /// more than one position through gateways, so this picks the lowest one
+    /// for everything aiming at or from an entity to agree on.
+    pub fn position_of(&self, entity: &Entity) -> Option<IVec2> {
+        self.seeing
+            .get(entity)?
+            .iter()
+            .min_by_key(|pos| (pos.x, pos.y))
+            .copied()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryComponents {
     pub position: Position,
     pub renderable: Renderable,
@@ -187,15 +203,28 @@ pub struct MemoryComponents {
     pub player: Option<Player>,
 }
 
+/// Positioned in the coordinates of the remembered room.
+impl From<&MemoryComponents> for api::Components {
+    fn from(memory: &MemoryComponents) -> Self {
+        Self {
+            positions: vec![memory.position.coordinates.into()],
+            renderable: Some((&memory.renderable).into()),
+            door: memory.door.as_ref().map(|it| it.into()),
+            wall: memory.wall.as_ref().map(|it| it.into()),
+            memory: Some(api::MemoryComponent {}),
+        }
+    }
+}
+
 #[derive(Component, Default, Debug, Clone)]
 pub struct Memory(pub HashMap<Entity, MemoryComponents>);
 
-#[derive(Component, Default, Debug, Clone)]
+#[derive(Component, Default, Debug, Clone, Serialize, Deserialize)]
 pub struct AIMemory {
     pub enemy: Option<Position>,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub enum AI {
     None,
     ServBot,
@@ -207,19 +236,52 @@ impl Default for AI {
     }
 }
 
-#[derive(Default, Component)]
+impl AI {
+    /// Whether NPCs of this kind can open doors of `kind`, unless locked.
+    pub fn opens(&self, kind: DoorKind) -> bool {
+        match self {
+            AI::None => false,
+            AI::ServBot => !matches!(kind, DoorKind::Bulkhead),
+        }
+    }
+
+    /// Whether NPCs of this kind investigate noise.
+    pub fn hears(&self) -> bool {
+        match self {
+            AI::None => false,
+            AI::ServBot => true,
+        }
+    }
+}
+
+/// Energy an actor gains per tick, making it act more or less often than
+/// others.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Speed(pub u16);
+
+impl Speed {
+    pub const NORMAL: Self = Self(10);
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+#[derive(Default, Component, Clone, Serialize, Deserialize)]
 pub struct Opaque;
 
-#[derive(Default, Component)]
+#[derive(Default, Component, Clone, Serialize, Deserialize)]
 pub struct Solid;
 
-#[derive(Default, Component)]
+#[derive(Default, Component, Clone)]
 pub struct Item {
     pub owner: Option<Entity>,
 }
 
 #[allow(dead_code)]
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, Serialize, Deserialize)]
 pub enum AttackKind {
     Kinetic,
     Beam,
@@ -229,7 +291,7 @@ pub enum AttackKind {
     Gas,
 }
 
-#[derive(Component, Clone, Debug, Copy)]
+#[derive(Component, Clone, Debug, Copy, Serialize, Deserialize)]
 pub struct Damage {
     pub attack: AttackKind,
     pub amount: u16,
@@ -238,21 +300,21 @@ pub struct Damage {
 }
 
 #[allow(dead_code)]
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, Serialize, Deserialize)]
 pub enum MeleeWeaponKind {
     LeadPipe,
     LaserRapier,
     Appendages,
 }
 
-#[derive(Component, Clone, Debug, Copy)]
+#[derive(Component, Clone, Debug, Copy, Serialize, Deserialize)]
 pub struct MeleeWeapon {
     pub kind: MeleeWeaponKind,
     pub damage: Damage,
 }
 
 #[allow(dead_code)]
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub enum ProjectileKind {
     RubberSlug,
     Needle,
@@ -263,14 +325,14 @@ pub enum ProjectileKind {
     Grenade,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Projectile {
     pub kind: ProjectileKind,
     pub damage: Damage,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum OperationKind {
     SemiAutomatic,
     Automatic(u16),
@@ -286,7 +348,7 @@ impl OperationKind {
 }
 
 #[allow(dead_code)]
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub enum RangedWeapon {
     Projectile(ProjectileGun),
     Energy(EnergyGun),
@@ -309,14 +371,14 @@ impl RangedWeapon {
     }
 }
 
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ProjectileGun {
     pub kind: ProjectileGunKind,
     pub operation: OperationKind,
 }
 
 #[allow(dead_code)]
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Magazine {
     pub gun: ProjectileGunKind,
     pub projectile: Projectile,
@@ -325,7 +387,7 @@ pub struct Magazine {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProjectileGunKind {
     RiotGun,
     DartPistol,
@@ -338,7 +400,7 @@ pub enum ProjectileGunKind {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum EnergyGunKind {
     StunGun,
     Sparq,
@@ -348,6 +410,7 @@ pub enum EnergyGunKind {
 }
 
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum BeamKind {
     Stun,
     SparqBeam,
@@ -356,13 +419,13 @@ pub enum BeamKind {
     Plasma,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Beam {
     pub kind: BeamKind,
     pub damage: Damage,
 }
 
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct EnergyGun {
     pub kind: EnergyGunKind,
     pub operation: OperationKind,
@@ -371,23 +434,42 @@ pub struct EnergyGun {
     pub max: u8,
 }
 
-#[derive(Component)]
+impl EnergyGun {
+    /// Battery charge drawn by a shot at `power`.
+    pub fn cost(&self, power: u8) -> u16 {
+        (power as f32 * self.efficiency).ceil() as u16
+    }
+
+    /// Damage of a shot at `power`, in proportion to the maximum setting.
+    pub fn damage(&self, power: u8) -> Damage {
+        Damage {
+            amount: (self.damage.amount as u32 * power as u32 / self.max as u32) as u16,
+            ..self.damage
+        }
+    }
+}
+
+/// Power level an energy gun is set to, its maximum if absent.
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PowerSetting(pub u8);
+
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct PersonalBattery {
     pub max: u16,
     pub charge: u16,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct MeleeSlot;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct GunSlot;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct Equipped;
 
 #[allow(dead_code)]
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub enum VulnerableKind {
     None,
     Avian,
@@ -408,7 +490,7 @@ impl Default for VulnerableKind {
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct Vulnerable {
     pub kind: VulnerableKind,
     pub hp: u16,
@@ -417,21 +499,38 @@ pub struct Vulnerable {
     pub armor: u8,
 }
 
-#[derive(Component, Debug, Clone, Copy)]
+/// A rough estimate of how hurt something is, as it looks from the outside.
+impl From<&Vulnerable> for api::examined_entity::Health {
+    fn from(vulnerable: &Vulnerable) -> Self {
+        let ratio = vulnerable.hp as f32 / vulnerable.max.max(1) as f32;
+
+        if vulnerable.hp >= vulnerable.max {
+            Self::Unhurt
+        } else if ratio >= 0.5 {
+            Self::Wounded
+        } else if ratio >= 0.2 {
+            Self::BadlyWounded
+        } else {
+            Self::NearDeath
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Alive {
     Human,
     ServBot,
 }
 
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Destructible {
     Server,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct RoomSpawner;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     North,
     East,
@@ -448,6 +547,15 @@ impl Direction {
             Self::West => Self::East,
         }
     }
+
+    /// The direction after mirroring along the north-south axis.
+    pub fn mirror(&self) -> Self {
+        match self {
+            Self::East => Self::West,
+            Self::West => Self::East,
+            it => *it,
+        }
+    }
 }
 
 impl TryFrom<(i32, i32)> for Direction {
@@ -475,7 +583,7 @@ impl From<Direction> for (i32, i32) {
     }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Gateway {
     pub twin: Entity,
     pub direction: Direction,
@@ -504,13 +612,53 @@ impl Gateway {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Lock {
     pub active: bool,
     pub locked: HashSet<Entity>,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone)]
 pub struct Switch {
     pub targets: Vec<Entity>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ion_rifle() -> EnergyGun {
+        EnergyGun {
+            kind: EnergyGunKind::IonPulse,
+            operation: OperationKind::SemiAutomatic,
+            damage: Damage {
+                attack: AttackKind::Beam,
+                amount: 18,
+                penetration: 35,
+                offense: 6,
+            },
+            efficiency: 3.6,
+            max: 10,
+        }
+    }
+
+    #[test]
+    fn test_energy_gun_cost() {
+        let gun = ion_rifle();
+        assert_eq!(gun.cost(1), 4);
+        assert_eq!(gun.cost(5), 18);
+        assert_eq!(gun.cost(10), 36);
+    }
+
+    #[test]
+    fn test_energy_gun_damage() {
+        let gun = ion_rifle();
+        assert_eq!(gun.damage(1).amount, 1);
+        assert_eq!(gun.damage(5).amount, 9);
+        assert_eq!(gun.damage(10).amount, 18);
+
+        let damage = gun.damage(5);
+        assert_eq!(damage.penetration, 35);
+        assert_eq!(damage.offense, 6);
+    }
+}
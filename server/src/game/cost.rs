@@ -0,0 +1,27 @@
+//! Energy actions cost, relative to a turn's worth of energy at normal speed.
+
+use super::resource::TURN;
+
+const fn turns(percent: i32) -> u16 {
+    (TURN * percent / 100) as u16
+}
+
+pub const MOVE: u16 = turns(100);
+pub const MELEE: u16 = turns(100);
+pub const DOOR: u16 = turns(100);
+pub const SWITCH: u16 = turns(100);
+pub const BEAM: u16 = turns(100);
+pub const RELOAD: u16 = turns(150);
+pub const EJECT: u16 = turns(50);
+pub const PICK_UP: u16 = turns(100);
+pub const DROP: u16 = turns(50);
+pub const EQUIP: u16 = turns(100);
+pub const UNEQUIP: u16 = turns(50);
+/// Doing nothing, or failing to do anything.
+pub const WAIT: u16 = turns(100);
+
+/// Firing `rounds` from a projectile gun, automatic weapons taking longer the
+/// more rounds they fire at once.
+pub const fn shoot(rounds: u16) -> u16 {
+    turns(100) + rounds.saturating_sub(1) * turns(25)
+}
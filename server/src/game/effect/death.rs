@@ -16,7 +16,7 @@ pub fn effect(
 
     commands
         .entity(*actor)
-        .remove_bundle::<(Alive, Solid, Opaque, Vulnerable, Sight, Memory, AI)>();
+        .remove_bundle::<(Alive, Solid, Opaque, Vulnerable, Sight, Memory, AI, Speed)>();
 
     let sight = sight.single();
 
@@ -0,0 +1,52 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, *};
+
+pub fn effect(
+    action: Res<Action>,
+    mut commands: Commands,
+    positions: Query<&Position>,
+    mut items: Query<&mut Item>,
+    mut magazines: Query<(Entity, &mut Magazine)>,
+) {
+    match action.as_ref() {
+        Action::PickUp(PickUpAction::Take { actor, item }) => {
+            commands.entity(*item).remove::<Position>();
+            hand_over(*item, Some(*actor), &mut items, &magazines);
+        }
+        Action::Drop(DropAction::Put { actor, item }) => {
+            let position = *positions.get(*actor).unwrap();
+            commands.entity(*item).insert(position).remove::<Equipped>();
+
+            // Dropping an attached magazine ejects it first.
+            if let Ok((_, mut magazine)) = magazines.get_mut(*item) {
+                magazine.attached = None;
+            }
+
+            hand_over(*item, None, &mut items, &magazines);
+        }
+        Action::Equip(EquipAction::Wield { item, .. }) => {
+            commands.entity(*item).insert(Equipped);
+        }
+        Action::Unequip(UnequipAction::Stow { item, .. }) => {
+            commands.entity(*item).remove::<Equipped>();
+        }
+        _ => {}
+    }
+}
+
+/// Hands `item` over to `owner`, along with any magazine attached to it.
+fn hand_over(
+    item: Entity,
+    owner: Option<Entity>,
+    items: &mut Query<&mut Item>,
+    magazines: &Query<(Entity, &mut Magazine)>,
+) {
+    let attached = magazines
+        .iter()
+        .filter_map(|(entity, magazine)| (magazine.attached == Some(item)).then_some(entity));
+
+    for entity in std::iter::once(item).chain(attached) {
+        items.get_mut(entity).unwrap().owner = owner;
+    }
+}
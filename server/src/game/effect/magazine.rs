@@ -0,0 +1,21 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, *};
+
+pub fn effect(action: Res<Action>, mut magazines: Query<(&mut Magazine, &mut Item)>) {
+    let (actor, weapon, magazine) = match action.as_ref() {
+        Action::Reload(ReloadAction::Attach {
+            actor,
+            weapon,
+            magazine,
+        }) => (*actor, Some(*weapon), *magazine),
+        Action::EjectMagazine(EjectMagazineAction::Detach {
+            actor, magazine, ..
+        }) => (*actor, None, *magazine),
+        _ => return,
+    };
+
+    let (mut magazine, mut item) = magazines.get_mut(magazine).unwrap();
+    magazine.attached = weapon;
+    item.owner = Some(actor);
+}
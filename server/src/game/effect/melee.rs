@@ -40,7 +40,12 @@ pub fn effect(
 
     events.0.push(api::Event {
         event: Some(api::event::Event::Hit(api::HitEvent {
-            positions: positions.iter().cloned().map_into().collect(),
+            positions: positions
+                .iter()
+                .sorted_by_key(|pos| (pos.x, pos.y))
+                .cloned()
+                .map_into()
+                .collect(),
             kind: kind as i32,
             direction: direction as i32,
         })),
@@ -4,16 +4,20 @@ mod door;
 mod gateway;
 mod god_mode;
 mod health;
+mod inventory;
 mod lock;
 mod log;
+mod magazine;
 mod melee;
 mod memorize;
 mod r#move;
+mod power;
 mod render;
 mod room;
 mod shoot;
 mod spot;
 mod state;
+mod turn;
 mod view;
 
 pub use self::log::effect as log;
@@ -25,15 +29,19 @@ pub use door::propagate as door_propagate;
 pub use gateway::effect as gateway;
 pub use god_mode::effect as god_mode;
 pub use health::effect as health;
+pub use inventory::effect as inventory;
 pub use lock::activate as lock_activate;
 pub use lock::deactivate as lock_deactivate;
+pub use magazine::effect as magazine;
 pub use melee::effect as melee;
 pub use memorize::ai as ai_memorize;
 pub use memorize::effect as memorize;
+pub use power::effect as power;
 pub use r#move::effect as r#move;
 pub use render::effect as render;
 pub use room::effect as room;
 pub use shoot::effect as shoot;
 pub use spot::effect as spot;
 pub use state::effect as state;
+pub use turn::effect as turn;
 pub use view::effect as view;
@@ -0,0 +1,12 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, *};
+
+pub fn effect(action: Res<Action>, mut commands: Commands) {
+    let (weapon, power) = match action.as_ref() {
+        Action::Power(PowerAction::Set { weapon, power }) => (*weapon, *power),
+        _ => return,
+    };
+
+    commands.entity(weapon).insert(PowerSetting(power));
+}
@@ -7,30 +7,46 @@ pub fn effect(
     action: Res<Action>,
     mut events: ResMut<Events>,
     mut magazines: Query<&mut Magazine>,
+    mut batteries: Query<&mut PersonalBattery>,
     sight: Query<&Sight, With<Player>>,
     weapons: Query<&RangedWeapon>,
 ) {
-    let (actor, target, weapon, magazine) = match action.as_ref() {
+    let (actor, weapon, impact) = match action.as_ref() {
         Action::Shoot(ShootAction::DispatchProjectile {
             actor,
-            target,
             weapon,
             magazine,
-        }) => (actor, target, weapon, magazine),
+            impact,
+            ..
+        }) => {
+            magazines.get_mut(*magazine).unwrap().amount -= 1;
+            (actor, weapon, impact)
+        }
+        Action::Shoot(ShootAction::DispatchBeam {
+            actor,
+            weapon,
+            power,
+            impact,
+            ..
+        }) => {
+            let gun = weapons.get(*weapon).ok().and_then(|w| w.energy()).unwrap();
+            let mut battery = batteries.get_mut(*actor).unwrap();
+            battery.charge = battery.charge.saturating_sub(gun.cost(*power));
+            (actor, weapon, impact)
+        }
         _ => return,
     };
 
-    magazines.get_mut(*magazine).unwrap().amount -= 1;
-
     let sight = sight.single();
 
-    let source = match sight.seeing.get(actor) {
-        Some(it) => it.iter().next().unwrap().to_owned().into(),
+    let source = match sight.position_of(actor) {
+        Some(it) => it,
         None => return,
     };
 
-    let target = match sight.seeing.get(target) {
-        Some(it) => it.iter().next().unwrap().to_owned().into(),
+    // The impact is relative to the actor, who is seen at `source`.
+    let target = match impact {
+        Some(it) => source + *it,
         None => return,
     };
 
@@ -54,15 +70,15 @@ pub fn effect(
             EnergyGunKind::StunGun => todo!(),
             EnergyGunKind::Sparq => todo!(),
             EnergyGunKind::Blaster => todo!(),
-            EnergyGunKind::IonPulse => todo!(),
+            EnergyGunKind::IonPulse => (ShootKind::Beam, api::shoot_event::ShootSound::IonRifle),
             EnergyGunKind::Plasma => todo!(),
         },
     };
 
     events.0.push(api::Event {
         event: Some(api::event::Event::Shoot(api::ShootEvent {
-            source: Some(source),
-            target: Some(target),
+            source: Some(source.into()),
+            target: Some(target.into()),
             kind: kind as i32,
             sound: sound as i32,
         })),
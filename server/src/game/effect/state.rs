@@ -16,12 +16,19 @@ pub fn effect(
 
     let player = player.single();
 
-    *state_res = state.clone();
+    let state = api::State {
+        version: state_res.version + 1,
+        ..state.clone()
+    };
+    let diff = state_res.diff(&state);
+
+    *state_res = state;
 
     events.0.push(api::Event {
         event: Some(api::event::Event::State(api::StateUpdateEvent {
             player: player.id(),
-            state: Some(state.clone()),
+            state: None,
+            diff: Some(diff),
         })),
     });
 }
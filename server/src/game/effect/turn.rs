@@ -0,0 +1,27 @@
+use bevy_ecs::prelude::*;
+
+use crate::game::{component::*, resource::Scheduler, *};
+
+pub fn effect(
+    action: Res<Action>,
+    mut scheduler: ResMut<Scheduler>,
+    actors: Query<(Entity, &Speed)>,
+    player: Query<(), With<Player>>,
+) {
+    match action.as_ref() {
+        Action::EndTurn(EndTurnAction { actor, cost }) => {
+            if scheduler.ends(*actor, player.contains(*actor)) {
+                scheduler.end(*actor, *cost);
+            }
+        }
+        Action::Turn(TurnAction::Start { actor, ticks }) => {
+            scheduler.start(
+                actors.iter().map(|(actor, speed)| (actor, *speed)),
+                *ticks,
+                *actor,
+                player.contains(*actor),
+            );
+        }
+        _ => {}
+    }
+}
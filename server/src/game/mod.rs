@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
 use std::fmt::Display;
 use std::time::Instant;
 
@@ -11,15 +12,20 @@ use itertools::Itertools;
 use rand::prelude::*;
 use thiserror::Error;
 
-use self::room::{Room, RoomAsset, RoomId, Rooms};
+use self::room::{Layout, Room, RoomId, Rooms};
+
+pub use self::save::{SaveError, SaveGame};
 
 mod behavior;
 mod bundle;
 mod component;
+mod cost;
 mod effect;
+mod noise;
 mod pathfinding;
 mod resource;
 mod room;
+mod save;
 
 type BoxedBehavior = BoxedSystem<(), Status>;
 
@@ -29,8 +35,81 @@ pub struct Game {
     effects: Vec<BoxedSystem>,
 }
 
+/// Room the player starts in unless a level is given.
+const START_ROOM: &str = "hibernation";
+
+/// Parameters a new game is started with.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    /// Seed for all randomness in the game, picked at random when unset.
+    pub seed: Option<u64>,
+    /// Room asset to play as a fixed level instead of starting a procedurally
+    /// generated station.
+    pub level: Option<String>,
+}
+
 impl Default for Game {
     fn default() -> Self {
+        Self::new(Default::default()).expect("Start room asset exists")
+    }
+}
+
+impl Game {
+    pub fn new(settings: Settings) -> Result<Self, UnknownLevel> {
+        let seed = settings.seed.unwrap_or_else(|| thread_rng().gen());
+        log::info!("Starting new game with seed {}", seed);
+
+        let game = Self::setup(seed);
+        let name = settings.level.as_deref().unwrap_or(START_ROOM);
+        let room = match game.world.resource::<Rooms>().get(name) {
+            Some(room) => room.clone(),
+            None => return Err(UnknownLevel(name.to_string())),
+        };
+
+        Ok(game.start(room))
+    }
+
+    /// Spawns `room` as the first room of a freshly set up game.
+    fn start(mut self, room: Room) -> Self {
+        let room_id = RoomId(0);
+        let spawner = self
+            .world
+            .spawn()
+            .insert(component::Position {
+                coordinates: ivec2(0, 0),
+                room: room_id,
+            })
+            .insert(component::RoomSpawner)
+            .id();
+
+        self.input(Action::SpawnRoom(RoomSpawnAction {
+            target: spawner,
+            room,
+            id: room_id,
+            origin: ivec2(0, 0),
+        }));
+        self.input(Action::View(ViewAction::All));
+
+        self
+    }
+
+    /// A game started in `base`, generating rooms only from `rooms`.
+    #[cfg(test)]
+    fn with_rooms(base: &str, rooms: &[&str]) -> Self {
+        let mut game = Self::setup(0);
+        game.world.insert_resource(
+            rooms
+                .iter()
+                .enumerate()
+                .map(|(i, room)| (i.to_string(), room.parse().unwrap()))
+                .collect::<Rooms>(),
+        );
+
+        game.start(base.parse().unwrap())
+    }
+
+    /// Builds a game with an empty world, ready for spawning into.
+    fn setup(seed: u64) -> Self {
         let mut world = World::new();
 
         behavior::radial_lines(&mut world);
@@ -43,13 +122,17 @@ impl Default for Game {
         world.init_resource::<resource::Log>();
         world.init_resource::<Rooms>();
         world.init_resource::<RoomId>();
+        world.init_resource::<Layout>();
         world.init_resource::<api::State>();
+        world.init_resource::<resource::Scheduler>();
+        world.insert_resource(resource::Random::new(seed));
 
         let mut behaviors = vec![
             Box::new(IntoSystem::into_system(behavior::dwim_move)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::dwim_close)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::dwim_shoot)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::ai)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::turn)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::god_mode)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::r#move)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::door)) as BoxedBehavior,
@@ -57,6 +140,7 @@ impl Default for Game {
             Box::new(IntoSystem::into_system(behavior::view)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::view_all)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::spot)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::hearing)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::memorize)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::ai_memorize)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::melee_intent)) as BoxedBehavior,
@@ -64,6 +148,15 @@ impl Default for Game {
             Box::new(IntoSystem::into_system(behavior::shoot_intent)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::shoot_projectile)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::dispatch_projectile)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::shoot_energy)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::dispatch_beam)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::power)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::reload)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::eject_magazine)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::pick_up)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::drop_item)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::equip)) as BoxedBehavior,
+            Box::new(IntoSystem::into_system(behavior::unequip)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::combat_damage)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::combat_hit)) as BoxedBehavior,
             Box::new(IntoSystem::into_system(behavior::death)) as BoxedBehavior,
@@ -80,6 +173,7 @@ impl Default for Game {
 
         let mut effects = vec![
             Box::new(IntoSystem::into_system(effect::r#move)) as BoxedSystem,
+            Box::new(IntoSystem::into_system(effect::turn)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::god_mode)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::room)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::door_open)) as BoxedSystem,
@@ -87,6 +181,9 @@ impl Default for Game {
             Box::new(IntoSystem::into_system(effect::door_propagate)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::melee)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::shoot)) as BoxedSystem,
+            Box::new(IntoSystem::into_system(effect::power)) as BoxedSystem,
+            Box::new(IntoSystem::into_system(effect::magazine)) as BoxedSystem,
+            Box::new(IntoSystem::into_system(effect::inventory)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::health)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::death)) as BoxedSystem,
             Box::new(IntoSystem::into_system(effect::destroy)) as BoxedSystem,
@@ -107,33 +204,26 @@ impl Default for Game {
             (*effect).apply_buffers(&mut world);
         }
 
-        let room_id = RoomId(0);
-
-        let spawner = world
-            .spawn()
-            .insert(component::Position {
-                coordinates: ivec2(0, 0),
-                room: room_id,
-            })
-            .insert(component::RoomSpawner)
-            .id();
-        let room = RoomAsset::Hibernation.load();
-
-        let mut game = Self {
+        Self {
             world,
             behaviors,
             effects,
-        };
+        }
+    }
 
-        game.input(Action::SpawnRoom(RoomSpawnAction {
-            target: spawner,
-            room,
-            id: room_id,
-        }));
+    /// Restores a game from `save`.
+    pub fn load(save: SaveGame) -> Self {
+        let mut game = Self::setup(save.seed());
+
+        save.restore(&mut game.world);
         game.input(Action::View(ViewAction::All));
 
         game
     }
+
+    pub fn save(&mut self) -> SaveGame {
+        SaveGame::capture(&mut self.world)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -141,7 +231,8 @@ pub enum Action {
     None,
     Dwim(DwimAction),
     AI(Entity),
-    EndTurn(Entity),
+    EndTurn(EndTurnAction),
+    Turn(TurnAction),
     GodMode(GodModeAction),
     Move(MoveAction),
     OpenDoor(OpenDoorAction),
@@ -150,9 +241,17 @@ pub enum Action {
     Memorize(MemorizeAction),
     AIMemorize(AIMemorizeAction),
     Spot(SpotAction),
+    Noise(NoiseAction),
     Log(String),
     Melee(MeleeAttackAction),
     Shoot(ShootAction),
+    Power(PowerAction),
+    Reload(ReloadAction),
+    EjectMagazine(EjectMagazineAction),
+    PickUp(PickUpAction),
+    Drop(DropAction),
+    Equip(EquipAction),
+    Unequip(UnequipAction),
     Hit(HitAction),
     Damage(DamageAction),
     HealthLoss(HealthLossAction),
@@ -179,6 +278,7 @@ impl Display for Action {
             Action::Dwim(_) => "Dwim",
             Action::AI(_) => "AI",
             Action::EndTurn(_) => "EndTurn",
+            Action::Turn(_) => "Turn",
             Action::GodMode(_) => "GodMode",
             Action::Move(_) => "Move",
             Action::OpenDoor(_) => "OpenDoor",
@@ -187,9 +287,17 @@ impl Display for Action {
             Action::Memorize(_) => "Memorize",
             Action::AIMemorize(_) => "AIMemorize",
             Action::Spot(_) => "Spot",
+            Action::Noise(_) => "Noise",
             Action::Log(_) => "Log",
             Action::Melee(_) => "Melee",
             Action::Shoot(_) => "Shoot",
+            Action::Power(_) => "Power",
+            Action::Reload(_) => "Reload",
+            Action::EjectMagazine(_) => "EjectMagazine",
+            Action::PickUp(_) => "PickUp",
+            Action::Drop(_) => "Drop",
+            Action::Equip(_) => "Equip",
+            Action::Unequip(_) => "Unequip",
             Action::Hit(_) => "Hit",
             Action::Damage(_) => "Damage",
             Action::HealthLoss(_) => "HealthLoss",
@@ -207,6 +315,21 @@ impl Display for Action {
     }
 }
 
+/// `actor` is done, having spent `cost` energy.
+#[derive(Debug, Clone, Copy)]
+pub struct EndTurnAction {
+    actor: Entity,
+    cost: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TurnAction {
+    /// Picks who takes the next turn.
+    Next,
+    /// Lets `ticks` pass, then hands the turn to `actor`.
+    Start { actor: Entity, ticks: u32 },
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ActivateLockAction {
     actor: Entity,
@@ -319,9 +442,76 @@ pub enum ShootAction {
         target: Entity,
         weapon: Entity,
         magazine: Entity,
+        /// Where the shot ends up relative to the actor, traced on dispatch.
+        impact: Option<IVec2>,
+    },
+    EnergyGun {
+        actor: Entity,
+        target: Entity,
+        weapon: Entity,
+    },
+    DispatchBeam {
+        actor: Entity,
+        target: Entity,
+        weapon: Entity,
+        power: u8,
+        /// Where the beam ends up relative to the actor, traced on dispatch.
+        impact: Option<IVec2>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PowerAction {
+    Intent { power: u8 },
+    Set { weapon: Entity, power: u8 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ReloadAction {
+    Intent,
+    Attach {
+        actor: Entity,
+        weapon: Entity,
+        magazine: Entity,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EjectMagazineAction {
+    Intent,
+    Detach {
+        actor: Entity,
+        weapon: Entity,
+        magazine: Entity,
     },
 }
 
+/// Items in intents are entity ids, still to be validated against what the
+/// player carries or stands on.
+#[derive(Debug, Clone, Copy)]
+pub enum PickUpAction {
+    Intent { item: Option<u32> },
+    Take { actor: Entity, item: Entity },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DropAction {
+    Intent { item: u32 },
+    Put { actor: Entity, item: Entity },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EquipAction {
+    Intent { item: u32 },
+    Wield { actor: Entity, item: Entity },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum UnequipAction {
+    Intent { item: u32 },
+    Stow { actor: Entity, item: Entity },
+}
+
 #[derive(Debug, Clone)]
 pub enum DwimAction {
     UpLeft,
@@ -333,7 +523,86 @@ pub enum DwimAction {
     DownLeft,
     Left,
     Close,
-    Shoot,
+    Shoot(Option<ShootTarget>),
+}
+
+/// Target of a shot as requested by the player, still to be validated against
+/// what they can see.
+#[derive(Debug, Clone, Copy)]
+pub enum ShootTarget {
+    /// An entity by its id.
+    Entity(u32),
+    /// A position relative to the player.
+    Position(IVec2),
+}
+
+#[derive(Debug, Error)]
+pub enum InvalidAction {
+    #[error("Action not set")]
+    Unset,
+    #[error("Dwim index out of bounds")]
+    Dwim,
+    #[error("Power setting out of bounds")]
+    Power,
+}
+
+impl TryFrom<&api::ActionRequest> for Action {
+    type Error = InvalidAction;
+
+    fn try_from(request: &api::ActionRequest) -> Result<Self, Self::Error> {
+        use api::action_request::{Action as Request, DwimAction as Dwim};
+
+        let action = match request.action {
+            Some(Request::Dwim(direction)) => match Dwim::from_i32(direction) {
+                Some(Dwim::UpLeft) => Action::Dwim(DwimAction::UpLeft),
+                Some(Dwim::Up) => Action::Dwim(DwimAction::Up),
+                Some(Dwim::UpRight) => Action::Dwim(DwimAction::UpRight),
+                Some(Dwim::Right) => Action::Dwim(DwimAction::Right),
+                Some(Dwim::DownRight) => Action::Dwim(DwimAction::DownRight),
+                Some(Dwim::Down) => Action::Dwim(DwimAction::Down),
+                Some(Dwim::DownLeft) => Action::Dwim(DwimAction::DownLeft),
+                Some(Dwim::Left) => Action::Dwim(DwimAction::Left),
+                Some(Dwim::Close) => Action::Dwim(DwimAction::Close),
+                Some(Dwim::Shoot) => Action::Dwim(DwimAction::Shoot(None)),
+                None => return Err(InvalidAction::Dwim),
+            },
+            Some(Request::God(_)) => Action::GodMode(GodModeAction::Intent),
+            Some(Request::Power(api::action_request::PowerAction { power })) => {
+                let power = power.try_into().map_err(|_| InvalidAction::Power)?;
+                Action::Power(PowerAction::Intent { power })
+            }
+            Some(Request::Reload(_)) => Action::Reload(ReloadAction::Intent),
+            Some(Request::Eject(_)) => Action::EjectMagazine(EjectMagazineAction::Intent),
+            Some(Request::PickUp(api::action_request::PickUpAction { item })) => {
+                Action::PickUp(PickUpAction::Intent { item })
+            }
+            Some(Request::Drop(api::action_request::DropAction { item })) => {
+                Action::Drop(DropAction::Intent { item })
+            }
+            Some(Request::Equip(api::action_request::EquipAction { item })) => {
+                Action::Equip(EquipAction::Intent { item })
+            }
+            Some(Request::Unequip(api::action_request::UnequipAction { item })) => {
+                Action::Unequip(UnequipAction::Intent { item })
+            }
+            Some(Request::Shoot(ref shoot)) => {
+                use api::action_request::shoot_action::Target;
+
+                let target = match shoot.target {
+                    Some(Target::Entity(id)) => Some(ShootTarget::Entity(id)),
+                    Some(Target::Position(api::Position { x, y })) => {
+                        Some(ShootTarget::Position(ivec2(x, y)))
+                    }
+                    None => None,
+                };
+
+                Action::Dwim(DwimAction::Shoot(target))
+            }
+            None => return Err(InvalidAction::Unset),
+        };
+
+        Ok(action)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -360,6 +629,7 @@ pub struct RoomSpawnAction {
     target: Entity,
     id: RoomId,
     room: Room,
+    origin: IVec2,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -387,6 +657,14 @@ pub struct SpotAction {
     sound: api::spot_event::SpotSound,
 }
 
+/// Noise made by `actor` at `source`, heard as far as `loudness` carries.
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseAction {
+    actor: Entity,
+    source: component::Position,
+    loudness: u32,
+}
+
 #[derive(Default)]
 pub struct Reactions(pub Vec<Action>);
 #[derive(Default)]
@@ -403,6 +681,15 @@ pub enum Status {
 #[error("No player found")]
 pub struct NoPlayer;
 
+/// The player's sight and memory are gone with their death.
+#[derive(Debug, Error)]
+#[error("The player is dead")]
+pub struct PlayerDead;
+
+#[derive(Debug, Error)]
+#[error("No room asset named {0}")]
+pub struct UnknownLevel(pub String);
+
 impl Game {
     pub fn input(&mut self, action: Action) -> Vec<api::Event> {
         let mut actions = VecDeque::from([action]);
@@ -474,25 +761,12 @@ impl Game {
         let duration = Instant::now() - now;
         log::debug!("Time taken: {}µs", duration.as_micros());
 
-        // Deduplicate state events, last one wins; TODO move to impl
-        events.into_iter().fold(vec![], |mut acc, ev| {
-            match (acc.last(), &ev) {
-                (
-                    Some(api::Event {
-                        event: Some(api::event::Event::State(_)),
-                    }),
-                    api::Event {
-                        event: Some(api::event::Event::State(_)),
-                    },
-                ) => *acc.last_mut().unwrap() = ev,
-                _ => acc.push(ev),
-            }
-            acc
-        })
+        events
     }
 
     pub fn state(&mut self) -> Result<api::StateDumpResponse> {
         let state = self.world.resource::<api::State>().clone();
+        let ammo = state.status.as_ref().and_then(|it| it.ammo.clone());
         let player = self
             .world
             .query_filtered::<Entity, With<component::Player>>()
@@ -510,6 +784,378 @@ impl Game {
             player: player.id(),
             state: Some(state),
             log: Some(api::Log { entries: log }),
+            seed: self.seed(),
+            ammo,
+        })
+    }
+
+    /// Everything the player remembers, room by room, for an overview of the
+    /// explored station.
+    pub fn map(&mut self) -> Result<api::MapResponse> {
+        let (position, memory) = self
+            .world
+            .query_filtered::<(&component::Position, &component::Memory), With<component::Player>>()
+            .get_single(&self.world)
+            .map(|(position, memory)| (*position, memory.clone()))
+            .map_err(|_| PlayerDead)?;
+
+        let locked = self.locked();
+        let offsets = self.offsets(position.room);
+        let mut rooms: BTreeMap<RoomId, api::MapRoom> = BTreeMap::new();
+
+        // The player remembers where they have been, but knows where they are
+        for (entity, memory) in memory.0.iter().filter(|(_, it)| it.player.is_none()) {
+            let room = rooms
+                .entry(memory.position.room)
+                .or_insert_with(|| api::MapRoom {
+                    id: memory.position.room.0 as u32,
+                    offset: offsets.get(&memory.position.room).map(|it| (*it).into()),
+                    ..Default::default()
+                });
+
+            room.entities.insert(entity.id(), memory.into());
+
+            if memory.door.is_some() && self.is_locked(&locked, *entity) {
+                room.locked.push(entity.id());
+            }
+        }
+
+        for room in rooms.values_mut() {
+            room.locked.sort();
+        }
+
+        Ok(api::MapResponse {
+            player: Some(position.coordinates.into()),
+            rooms: rooms.into_values().collect(),
         })
     }
+
+    /// What the player sees at `position`, relative to them. Like the state
+    /// and the map, this only reads the world and takes no turn, so it's not
+    /// an `Action`.
+    pub fn examine(&mut self, position: IVec2) -> Result<api::ExamineResponse> {
+        let seen: Vec<_> = self
+            .world
+            .query_filtered::<&component::Sight, With<component::Player>>()
+            .get_single(&self.world)
+            .map_err(|_| PlayerDead)?
+            .seeing
+            .iter()
+            .filter(|(_, positions)| positions.contains(&position))
+            .map(|(entity, _)| *entity)
+            .sorted_by_key(|entity| entity.id())
+            .collect();
+
+        let locked = self.locked();
+        let loaded = self
+            .world
+            .query::<&component::Magazine>()
+            .iter(&self.world)
+            .fold(HashMap::new(), |mut acc, magazine| {
+                if let Some(gun) = magazine.attached {
+                    *acc.entry(gun).or_insert(0) += magazine.amount as u32;
+                }
+                acc
+            });
+
+        let entities = seen
+            .into_iter()
+            .map(|entity| {
+                let get = |entity| self.world.entity(entity);
+                let door = get(entity).get::<component::Door>();
+                let magazine = get(entity).get::<component::Magazine>();
+
+                let damage = match (
+                    get(entity).get::<component::MeleeWeapon>(),
+                    get(entity).get::<component::RangedWeapon>(),
+                    magazine,
+                ) {
+                    (Some(weapon), ..) => Some(weapon.damage.amount),
+                    (_, Some(component::RangedWeapon::Energy(gun)), _) => Some(gun.damage.amount),
+                    (.., Some(magazine)) => Some(magazine.projectile.damage.amount),
+                    _ => None,
+                };
+
+                let rounds = match get(entity).get::<component::RangedWeapon>() {
+                    Some(component::RangedWeapon::Projectile(_)) => {
+                        Some(loaded.get(&entity).copied().unwrap_or(0))
+                    }
+                    _ => magazine.map(|it| it.amount as u32),
+                };
+
+                api::ExaminedEntity {
+                    entity: entity.id(),
+                    description: get(entity)
+                        .get::<component::Description>()
+                        .map(|it| it.to_string())
+                        .unwrap_or_default(),
+                    open: door.map(|it| it.open),
+                    locked: door.is_some() && self.is_locked(&locked, entity),
+                    health: get(entity)
+                        .get::<component::Vulnerable>()
+                        .map(|it| api::examined_entity::Health::from(it) as i32),
+                    damage: damage.map(|it| it as u32),
+                    rounds,
+                }
+            })
+            .collect();
+
+        Ok(api::ExamineResponse { entities })
+    }
+
+    /// Offsets of all rooms connected to `room` through gateways, the way
+    /// the state projects them.
+    fn offsets(&mut self, room: RoomId) -> HashMap<RoomId, IVec2> {
+        let gateways: Vec<_> = self
+            .world
+            .query::<(&component::Position, &component::Gateway)>()
+            .iter(&self.world)
+            .map(|(pos, gateway)| (*pos, gateway.twin, gateway.direction))
+            .collect();
+
+        pathfinding::RoomGraph::new(gateways.into_iter().map(|(pos, twin, direction)| {
+            let twin = self.world.get::<component::Position>(twin).unwrap();
+            (pos, *twin, direction)
+        }))
+        .offsets(room)
+    }
+
+    /// Doors held shut by active locks.
+    fn locked(&mut self) -> HashSet<Entity> {
+        self.world
+            .query::<&component::Lock>()
+            .iter(&self.world)
+            .filter(|lock| lock.active)
+            .flat_map(|lock| lock.locked.iter().copied())
+            .collect()
+    }
+
+    /// Whether `door` is among the `locked` doors, bulkhead doors being
+    /// locked as a whole.
+    fn is_locked(&self, locked: &HashSet<Entity>, door: Entity) -> bool {
+        let door = self
+            .world
+            .get::<bevy_hierarchy::Parent>(door)
+            .map_or(door, |it| it.get());
+
+        locked.contains(&door)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.world.resource::<resource::Random>().seed()
+    }
+}
+
+/// Folds consecutive state events into one, diffs merging into the diff
+/// before them and full snapshots replacing whatever came before them.
+pub fn compact(events: Vec<api::Event>) -> Vec<api::Event> {
+    events.into_iter().fold(vec![], |mut acc, ev| {
+        match (acc.last_mut(), ev) {
+            (
+                Some(api::Event {
+                    event: Some(api::event::Event::State(last)),
+                }),
+                api::Event {
+                    event: Some(api::event::Event::State(next)),
+                },
+            ) if next.state.is_some() || last.state.is_none() => {
+                match (&mut last.diff, next.diff) {
+                    (Some(diff), Some(next_diff)) => {
+                        diff.merge(next_diff);
+                        last.player = next.player;
+                    }
+                    (_, diff) => {
+                        *last = api::StateUpdateEvent { diff, ..next };
+                    }
+                }
+            }
+            (_, ev) => acc.push(ev),
+        }
+        acc
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A diff removing one entity per version.
+    fn diff(base: u64, version: u64) -> api::Event {
+        api::Event {
+            event: Some(api::event::Event::State(api::StateUpdateEvent {
+                player: 1,
+                state: None,
+                diff: Some(api::StateDiff {
+                    base,
+                    version,
+                    removed: (base + 1..=version).map(|it| it as u32).collect(),
+                    ..Default::default()
+                }),
+            })),
+        }
+    }
+
+    fn snapshot(version: u64) -> api::Event {
+        api::Event {
+            event: Some(api::event::Event::State(api::StateUpdateEvent {
+                player: 1,
+                state: Some(api::State {
+                    version,
+                    ..Default::default()
+                }),
+                diff: None,
+            })),
+        }
+    }
+
+    fn log(entry: &str) -> api::Event {
+        api::Event {
+            event: Some(api::event::Event::Log(api::LogEvent {
+                entry: entry.to_string(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_compact_diffs() {
+        assert_eq!(compact(vec![diff(0, 1), diff(1, 2)]), vec![diff(0, 2)]);
+
+        // Only consecutive state events are folded
+        let events = vec![diff(0, 1), log("Bla"), diff(1, 2)];
+        assert_eq!(compact(events.clone()), events);
+    }
+
+    #[test]
+    fn test_compact_diff_then_snapshot() {
+        assert_eq!(
+            compact(vec![diff(0, 1), diff(1, 2), snapshot(3)]),
+            vec![snapshot(3)]
+        );
+    }
+
+    #[test]
+    fn test_compact_snapshot_then_diff() {
+        // A diff can't be folded into the snapshot it applies to
+        let events = vec![snapshot(1), diff(1, 2)];
+        assert_eq!(compact(events.clone()), events);
+
+        assert_eq!(
+            compact(vec![snapshot(1), diff(1, 2), diff(2, 3)]),
+            vec![snapshot(1), diff(1, 3)]
+        );
+    }
+
+    /// State positions are relative to the player's room, so only the player
+    /// moves in the state when the player takes a step.
+    #[test]
+    fn test_state_frame() {
+        let mut game = Game::setup(0).start(
+            "
+#####
+#@··#
+#####"
+                .parse()
+                .unwrap(),
+        );
+
+        let before = game.state().unwrap();
+        game.input(Action::Dwim(DwimAction::Right));
+        let after = game.state().unwrap();
+
+        let player = before.player;
+        let (before, after) = (before.state.unwrap(), after.state.unwrap());
+        assert_eq!(before.entities.len(), after.entities.len());
+
+        for (id, components) in before.entities.iter() {
+            let positions = &after.entities[id].positions;
+
+            if *id == player {
+                assert_eq!(
+                    components.positions,
+                    [api::PositionComponent { x: 1, y: 1 }]
+                );
+                assert_eq!(*positions, [api::PositionComponent { x: 2, y: 1 }]);
+            } else {
+                assert_eq!(components.positions, *positions);
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_offsets() {
+        let mut game = Game::with_rooms(
+            "
+#####
+#@··|
+#####",
+            &["#####\n|···|\n#####"],
+        );
+
+        let player = game
+            .world
+            .query_filtered::<Entity, With<component::Player>>()
+            .single(&game.world);
+        let spawner = game
+            .world
+            .query_filtered::<Entity, (With<component::RoomSpawner>, With<component::Door>)>()
+            .single(&game.world);
+        game.input(Action::OpenDoor(OpenDoorAction {
+            actor: player,
+            target: spawner,
+        }));
+
+        // Remembered rooms are offset the way the state projects them
+        let map = game.map().unwrap();
+        let state = game.state().unwrap().state.unwrap();
+        assert_eq!(map.rooms.len(), 2);
+
+        for room in map.rooms.iter() {
+            let offset = room.offset.clone().unwrap();
+
+            for (id, components) in room.entities.iter() {
+                let pos = &components.positions[0];
+                let projected = api::PositionComponent {
+                    x: offset.x + pos.x,
+                    y: offset.y + pos.y,
+                };
+                assert!(state.entities[id].positions.contains(&projected));
+            }
+        }
+
+        // Death takes the player's memory
+        game.world.entity_mut(player).remove::<component::Memory>();
+        assert!(game.map().is_err());
+    }
+
+    #[test]
+    fn test_examine() {
+        let mut game = Game::setup(0).start(
+            r#"+++
+[legend]
+"/" = { tile = "item", item = "lead-pipe" }
++++
+####
+#@/#
+####"#
+                .parse()
+                .unwrap(),
+        );
+
+        let examined = game.examine(ivec2(1, 0)).unwrap();
+        let descriptions: Vec<_> = examined
+            .entities
+            .iter()
+            .map(|it| it.description.as_str())
+            .collect();
+        assert!(descriptions.contains(&"a lead pipe"));
+        assert!(game.examine(ivec2(5, 5)).unwrap().entities.is_empty());
+
+        // Death takes the player's sight
+        let player = game
+            .world
+            .query_filtered::<Entity, With<component::Player>>()
+            .single(&game.world);
+        game.world.entity_mut(player).remove::<component::Sight>();
+        assert!(game.examine(ivec2(1, 0)).is_err());
+    }
 }
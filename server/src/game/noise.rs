@@ -0,0 +1,21 @@
+//! How loud actions are, in the number of steps they can be heard across.
+
+use super::component::DoorKind;
+
+pub const GUNFIRE: u32 = 20;
+pub const BEAM: u32 = 8;
+pub const MELEE: u32 = 6;
+pub const DEATH: u32 = 10;
+
+/// How much closed doors muffle noise.
+pub const CLOSED_DOOR: u32 = 6;
+/// How much noise fades passing through a gateway.
+pub const GATEWAY: u32 = 2;
+
+pub fn door(kind: DoorKind) -> u32 {
+    match kind {
+        DoorKind::Heavy => 8,
+        DoorKind::Bulkhead => 12,
+        DoorKind::Storage => 5,
+    }
+}
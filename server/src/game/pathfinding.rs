@@ -1,11 +1,13 @@
 use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashMap, HashSet},
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
 };
 
 use glam::{ivec2, IVec2};
 
+use super::component::{Direction, Position};
 use super::resource::Path;
+use super::room::RoomId;
 
 const SEARCH_LIMIT: u16 = u16::MAX;
 
@@ -43,6 +45,35 @@ pub fn euclidian_distance(a: &IVec2, b: &IVec2) -> f32 {
     (x.powf(2.0) + y.powf(2.0)).sqrt() as f32
 }
 
+/// Cells on the Bresenham line from `from` through `to`, excluding `from`.
+///
+/// The line doesn't stop at `to` but keeps going in the same direction, so
+/// callers have to limit it themselves.
+pub fn bresenham(from: IVec2, to: IVec2) -> impl Iterator<Item = IVec2> {
+    let (dx, dy) = ((to.x - from.x).abs(), (to.y - from.y).abs());
+    let step = ivec2((to.x - from.x).signum(), (to.y - from.y).signum());
+    let mut error = dx - dy;
+    let mut cell = from;
+
+    std::iter::from_fn(move || {
+        if dx == 0 && dy == 0 {
+            return None;
+        }
+
+        let e2 = 2 * error;
+        if e2 >= -dy {
+            error -= dy;
+            cell.x += step.x;
+        }
+        if e2 <= dx {
+            error += dx;
+            cell.y += step.y;
+        }
+
+        Some(cell)
+    })
+}
+
 pub fn moves() -> HashMap<IVec2, f32> {
     (-1..=1)
         .flat_map(|x: i32| {
@@ -61,6 +92,7 @@ pub fn moves() -> HashMap<IVec2, f32> {
 
 pub struct AStar {
     obstacles: HashSet<IVec2>,
+    penalties: HashMap<IVec2, f32>,
     moves: HashMap<IVec2, f32>,
 }
 
@@ -68,10 +100,18 @@ impl AStar {
     pub fn new(obstacles: HashSet<IVec2>) -> Self {
         Self {
             obstacles,
+            penalties: Default::default(),
             moves: moves(),
         }
     }
 
+    /// Makes entering any of `penalties` cost extra, e.g. for a door that has
+    /// to be opened first.
+    pub fn with_penalties(mut self, penalties: HashMap<IVec2, f32>) -> Self {
+        self.penalties = penalties;
+        self
+    }
+
     fn h(a: &IVec2, b: &IVec2) -> f32 {
         euclidian_distance(a, b)
     }
@@ -97,7 +137,7 @@ impl AStar {
             .iter()
             .filter_map(|(delta, weight)| {
                 let index = node.index + *delta;
-                let g = node.g + *weight;
+                let g = node.g + *weight + self.penalties.get(&index).unwrap_or(&0.0);
                 let closed_better = closed.get(&index).is_some_and(|closed_g| **closed_g <= g);
 
                 if (&index != goal && self.obstacles.contains(&index)) || closed_better {
@@ -148,13 +188,290 @@ impl AStar {
     }
 }
 
+/// A step along a path planned by `RoomGraph`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub delta: IVec2,
+    /// Where the step leads, which is in another room when passing a gateway.
+    pub position: Position,
+}
+
+/// A way from one room into another.
+#[derive(Debug, Clone, Copy)]
+struct Portal {
+    /// The gateway cell to step through from.
+    entrance: Position,
+    /// The step through the gateway.
+    delta: IVec2,
+    /// Where stepping through the gateway leads, next to its twin.
+    exit: Position,
+}
+
+/// Rooms connected by their gateways, for planning paths across rooms.
+///
+/// Paths are planned hierarchically: a search over the portals between rooms,
+/// getting from one portal to the next within a room with `AStar`.
+pub struct RoomGraph {
+    portals: HashMap<RoomId, Vec<Portal>>,
+}
+
+impl RoomGraph {
+    /// Builds the graph from gateways, given by their position, their twin's
+    /// position and direction.
+    pub fn new(gateways: impl IntoIterator<Item = (Position, Position, Direction)>) -> Self {
+        let mut portals: HashMap<RoomId, Vec<Portal>> = HashMap::new();
+
+        for (entrance, twin, direction) in gateways {
+            let (x, y): (i32, i32) = direction.into();
+            let delta = ivec2(x, y);
+
+            portals.entry(entrance.room).or_default().push(Portal {
+                entrance,
+                delta,
+                exit: twin + delta,
+            });
+        }
+
+        Self { portals }
+    }
+
+    fn portals(&self, room: &RoomId) -> impl Iterator<Item = &Portal> {
+        self.portals.get(room).into_iter().flatten()
+    }
+
+    /// Gateways are open towards the void beyond the room, seal them in
+    /// `obstacles` so searches within a room don't leak out.
+    fn seal(&self, obstacles: &mut HashMap<RoomId, HashSet<IVec2>>) {
+        for portal in self.portals.values().flatten() {
+            obstacles
+                .entry(portal.entrance.room)
+                .or_default()
+                .insert(portal.entrance.coordinates + portal.delta);
+        }
+    }
+
+    /// Cost of `steps`, with straight steps costing 2 and diagonal ones 3, in
+    /// addition to twice their `penalties`.
+    fn cost(steps: &[Step], penalties: &HashMap<RoomId, HashMap<IVec2, f32>>) -> u32 {
+        steps
+            .iter()
+            .map(|step| {
+                let penalty = penalties
+                    .get(&step.position.room)
+                    .and_then(|it| it.get(&step.position.coordinates))
+                    .unwrap_or(&0.0);
+
+                (step.delta.x.abs() + step.delta.y.abs() + 1) as u32 + (2.0 * penalty) as u32
+            })
+            .sum()
+    }
+
+    /// Plans a path from `start` to `goal`, avoiding `obstacles` in each room
+    /// and preferring to avoid `penalties`.
+    ///
+    /// A gateway cell and its twin are the same place, so a goal on either of
+    /// them is reached on the one closer by. The path doesn't include `start`,
+    /// so it's empty when there already.
+    pub fn search(
+        &self,
+        start: Position,
+        goal: Position,
+        mut obstacles: HashMap<RoomId, HashSet<IVec2>>,
+        penalties: HashMap<RoomId, HashMap<IVec2, f32>>,
+    ) -> Option<Vec<Step>> {
+        self.seal(&mut obstacles);
+
+        let blocked = |position: &Position| {
+            obstacles
+                .get(&position.room)
+                .is_some_and(|it| it.contains(&position.coordinates))
+        };
+
+        let twin = self
+            .portals(&goal.room)
+            .find_map(|portal| (portal.entrance == goal).then_some(portal.exit - portal.delta));
+        let goals: Vec<Position> = std::iter::once(goal).chain(twin).collect();
+
+        let astars: HashMap<RoomId, AStar> = obstacles
+            .clone()
+            .into_iter()
+            .map(|(room, obstacles)| {
+                let penalties = penalties.get(&room).cloned().unwrap_or_default();
+                (room, AStar::new(obstacles).with_penalties(penalties))
+            })
+            .collect();
+        let empty = AStar::new(Default::default());
+
+        let within = |from: &Position, to: &Position| -> Option<Vec<Step>> {
+            let astar = astars.get(&from.room).unwrap_or(&empty);
+            let path = astar.search(from.coordinates, to.coordinates)?;
+
+            Some(
+                path.windows(2)
+                    .map(|cells| Step {
+                        delta: cells[1] - cells[0],
+                        position: Position {
+                            coordinates: cells[1],
+                            room: from.room,
+                        },
+                    })
+                    .collect(),
+            )
+        };
+
+        // Dijkstra over the positions a path continues from, which are the
+        // start and portal exits, remembering the steps leading to each
+        let mut costs: HashMap<Position, u32> = HashMap::from([(start, 0)]);
+        let mut legs: HashMap<Position, (Position, Vec<Step>)> = HashMap::new();
+        let mut nodes = vec![start];
+        let mut fringe = BinaryHeap::from([(Reverse(0), 0)]);
+
+        while let Some((Reverse(cost), index)) = fringe.pop() {
+            let node = nodes[index];
+
+            if costs.get(&node).is_some_and(|best| **best < cost) {
+                continue;
+            }
+
+            if goals.contains(&node) {
+                let mut path = Vec::new();
+                let mut node = node;
+
+                while let Some((parent, steps)) = legs.remove(&node) {
+                    path.extend(steps.into_iter().rev());
+                    node = parent;
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            let mut next = Vec::new();
+
+            for goal in goals.iter().filter(|goal| goal.room == node.room) {
+                if let Some(steps) = within(&node, goal) {
+                    next.push((*goal, steps));
+                }
+            }
+
+            for portal in self.portals(&node.room) {
+                if blocked(&portal.entrance) || blocked(&portal.exit) {
+                    continue;
+                }
+
+                if let Some(mut steps) = within(&node, &portal.entrance) {
+                    steps.push(Step {
+                        delta: portal.delta,
+                        position: portal.exit,
+                    });
+                    next.push((portal.exit, steps));
+                }
+            }
+
+            for (position, steps) in next {
+                let cost = cost + Self::cost(&steps, &penalties);
+
+                if costs.get(&position).is_some_and(|best| **best <= cost) {
+                    continue;
+                }
+
+                costs.insert(position, cost);
+                legs.insert(position, (node, steps));
+                nodes.push(position);
+                fringe.push((Reverse(cost), nodes.len() - 1));
+            }
+        }
+
+        None
+    }
+
+    /// Spreads something like sound from `start` with `strength`, which
+    /// weakens by one for every step, and by `penalties` for entering a cell.
+    /// It doesn't get past `obstacles` but does pass through gateways.
+    ///
+    /// Returns the strength left at every position reached.
+    pub fn spread(
+        &self,
+        start: Position,
+        strength: u32,
+        mut obstacles: HashMap<RoomId, HashSet<IVec2>>,
+        penalties: HashMap<RoomId, HashMap<IVec2, u32>>,
+    ) -> HashMap<Position, u32> {
+        self.seal(&mut obstacles);
+
+        let moves = moves();
+        let mut reached = HashMap::from([(start, strength)]);
+        let mut nodes = vec![start];
+        let mut fringe = BinaryHeap::from([(strength, 0)]);
+
+        while let Some((strength, index)) = fringe.pop() {
+            let node = nodes[index];
+
+            if reached.get(&node).is_some_and(|best| **best > strength) {
+                continue;
+            }
+
+            let portals = self
+                .portals(&node.room)
+                .filter(|portal| portal.entrance == node)
+                .map(|portal| portal.exit);
+
+            for next in moves.keys().map(|delta| node + *delta).chain(portals) {
+                let blocked = obstacles
+                    .get(&next.room)
+                    .is_some_and(|it| it.contains(&next.coordinates));
+                let penalty = penalties
+                    .get(&next.room)
+                    .and_then(|it| it.get(&next.coordinates))
+                    .unwrap_or(&0);
+                let left = strength.saturating_sub(1 + penalty);
+
+                if blocked || left == 0 || reached.get(&next).is_some_and(|best| **best >= left) {
+                    continue;
+                }
+
+                reached.insert(next, left);
+                nodes.push(next);
+                fringe.push((left, nodes.len() - 1));
+            }
+        }
+
+        reached
+    }
+
+    /// Offsets projecting the coordinates of every room connected to `room`
+    /// into its frame, where a gateway and its twin overlap. Rooms reached
+    /// along more than one chain of gateways keep the offset of the shortest.
+    pub fn offsets(&self, room: RoomId) -> HashMap<RoomId, IVec2> {
+        let mut offsets = HashMap::from([(room, IVec2::ZERO)]);
+        let mut queue = VecDeque::from([room]);
+
+        while let Some(room) = queue.pop_front() {
+            let offset = offsets[&room];
+
+            for portal in self.portals(&room) {
+                offsets.entry(portal.exit.room).or_insert_with(|| {
+                    queue.push_back(portal.exit.room);
+                    offset + portal.entrance.coordinates + portal.delta - portal.exit.coordinates
+                });
+            }
+        }
+
+        offsets
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
 
     use glam::{ivec2, IVec2};
 
-    use super::{AStar, SEARCH_LIMIT};
+    use super::{bresenham, AStar, RoomGraph, Step, SEARCH_LIMIT};
+    use crate::game::{
+        component::{Direction, Position},
+        room::RoomId,
+    };
 
     fn extract_room(room: String) -> (HashSet<IVec2>, IVec2, IVec2) {
         let mut obstacles = HashSet::new();
@@ -260,4 +577,148 @@ mod test {
             None
         );
     }
+
+    #[test]
+    fn test_bresenham() {
+        assert_eq!(
+            bresenham(ivec2(0, 0), ivec2(4, 2))
+                .take(6)
+                .collect::<Vec<_>>(),
+            vec![
+                ivec2(1, 1),
+                ivec2(2, 1),
+                ivec2(3, 2),
+                ivec2(4, 2),
+                ivec2(5, 3),
+                ivec2(6, 3)
+            ]
+        );
+
+        for x in -5..=5 {
+            for y in -5..=5 {
+                let to = ivec2(x, y);
+                let steps = x.abs().max(y.abs()) as usize;
+
+                if steps == 0 {
+                    assert_eq!(bresenham(to, to).next(), None);
+                } else {
+                    assert_eq!(bresenham(ivec2(0, 0), to).nth(steps - 1), Some(to));
+                }
+            }
+        }
+    }
+
+    fn position(x: i32, y: i32, room: u16) -> Position {
+        Position {
+            coordinates: ivec2(x, y),
+            room: RoomId(room),
+        }
+    }
+
+    #[test]
+    fn test_room_graph() {
+        // Room 0 has a gateway in its east wall at (3, 1), leading into room 1
+        // through its west wall at (0, 1)
+        let graph = RoomGraph::new([
+            (position(3, 1, 0), position(0, 1, 1), Direction::East),
+            (position(0, 1, 1), position(3, 1, 0), Direction::West),
+        ]);
+        let search =
+            |start, goal| graph.search(start, goal, Default::default(), Default::default());
+        let step = |x, y, room| Step {
+            delta: ivec2(1, 0),
+            position: position(x, y, room),
+        };
+
+        assert_eq!(
+            search(position(1, 1, 0), position(2, 1, 1)),
+            Some(vec![
+                step(2, 1, 0),
+                step(3, 1, 0),
+                step(1, 1, 1),
+                step(2, 1, 1)
+            ])
+        );
+
+        // The twin of a gateway is the same place
+        assert_eq!(
+            search(position(1, 1, 0), position(0, 1, 1)),
+            Some(vec![step(2, 1, 0), step(3, 1, 0)])
+        );
+
+        assert_eq!(search(position(1, 1, 0), position(1, 1, 0)), Some(vec![]));
+
+        // A closed gateway door, which may be opened or not
+        let penalties = HashMap::from([(RoomId(0), HashMap::from([(ivec2(3, 1), 1.0)]))]);
+        assert_eq!(
+            graph.search(
+                position(1, 1, 0),
+                position(2, 1, 1),
+                Default::default(),
+                penalties
+            ),
+            search(position(1, 1, 0), position(2, 1, 1))
+        );
+
+        let obstacles = HashMap::from([(RoomId(0), HashSet::from([ivec2(3, 1)]))]);
+        assert_eq!(
+            graph.search(
+                position(1, 1, 0),
+                position(2, 1, 1),
+                obstacles,
+                Default::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spread() {
+        let graph = RoomGraph::new([
+            (position(3, 1, 0), position(0, 1, 1), Direction::East),
+            (position(0, 1, 1), position(3, 1, 0), Direction::West),
+        ]);
+
+        let reached = graph.spread(position(1, 1, 0), 5, Default::default(), Default::default());
+        assert_eq!(reached.get(&position(1, 1, 0)), Some(&5));
+        assert_eq!(reached.get(&position(3, 1, 0)), Some(&3));
+        assert_eq!(reached.get(&position(1, 1, 1)), Some(&2));
+        assert_eq!(reached.get(&position(2, 1, 1)), Some(&1));
+        assert_eq!(reached.get(&position(3, 1, 1)), None);
+        assert_eq!(reached.get(&position(4, 1, 0)), None);
+
+        // Muffled by a closed door in the gateway
+        let penalties = HashMap::from([(RoomId(0), HashMap::from([(ivec2(3, 1), 2)]))]);
+        let reached = graph.spread(position(1, 1, 0), 5, Default::default(), penalties);
+        assert_eq!(reached.get(&position(3, 1, 0)), Some(&1));
+        assert_eq!(reached.get(&position(1, 1, 1)), None);
+    }
+
+    #[test]
+    fn test_offsets() {
+        // Room 1 leads on into room 2 through its south wall at (1, 0)
+        let graph = RoomGraph::new([
+            (position(3, 1, 0), position(0, 1, 1), Direction::East),
+            (position(0, 1, 1), position(3, 1, 0), Direction::West),
+            (position(1, 0, 1), position(2, 4, 2), Direction::South),
+            (position(2, 4, 2), position(1, 0, 1), Direction::North),
+        ]);
+
+        assert_eq!(
+            graph.offsets(RoomId(0)),
+            HashMap::from([
+                (RoomId(0), ivec2(0, 0)),
+                (RoomId(1), ivec2(3, 0)),
+                (RoomId(2), ivec2(2, -4)),
+            ])
+        );
+        assert_eq!(
+            graph.offsets(RoomId(2)),
+            HashMap::from([
+                (RoomId(2), ivec2(0, 0)),
+                (RoomId(1), ivec2(1, 4)),
+                (RoomId(0), ivec2(-2, 4)),
+            ])
+        );
+    }
 }
@@ -1,17 +1,29 @@
-use std::{collections::HashSet, fmt::Display, slice::Iter};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    slice::Iter,
+};
 
+use bevy_ecs::entity::Entity;
 use glam::{ivec2, IVec2};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use super::component::Speed;
 
 pub type Path = Vec<IVec2>;
 
 #[derive(Debug)]
 pub struct RadialLines(pub HashSet<Path>);
 
-pub struct Deltas(pub HashSet<IVec2>);
+/// Unit steps around a cell, in a fixed order so iterating them is reproducible.
+pub struct Deltas(pub Vec<IVec2>);
 
 impl Deltas {
     pub fn neighbors() -> Self {
-        let deltas: HashSet<_> = (-1..=1)
+        let deltas = (-1..=1)
             .flat_map(|x| {
                 (-1..=1).filter_map(move |y| {
                     if x == 0 && y == 0 {
@@ -41,7 +53,7 @@ impl Default for Deltas {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Log(Vec<String>);
 
 impl Log {
@@ -55,3 +67,217 @@ impl Log {
         self.0.iter()
     }
 }
+
+/// The single source of randomness of a game. Everything random has to be drawn
+/// from here so that the seed and the sequence of actions determine a game.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "RandomState", into = "RandomState")]
+pub struct Random {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for Random {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Serialized form of `Random`, the seed and how far the stream has advanced.
+#[derive(Serialize, Deserialize)]
+struct RandomState {
+    seed: u64,
+    position: (u64, u64),
+}
+
+impl From<Random> for RandomState {
+    fn from(random: Random) -> Self {
+        let position = random.rng.get_word_pos();
+
+        Self {
+            seed: random.seed,
+            position: ((position >> 64) as u64, position as u64),
+        }
+    }
+}
+
+impl From<RandomState> for Random {
+    fn from(state: RandomState) -> Self {
+        let mut random = Self::new(state.seed);
+        let (high, low) = state.position;
+        let position = ((high as u128) << 64) | low as u128;
+        random.rng.set_word_pos(position);
+        random
+    }
+}
+
+/// Energy an actor needs to take a turn.
+pub const TURN: i32 = 100;
+
+/// Turn order of all actors, i.e. entities with a `Speed`.
+///
+/// Time passes in ticks, giving every actor their speed in energy. Once an
+/// actor has enough energy for a turn, it acts and pays for that with the cost
+/// of its action, so fast actors act more often and costly actions delay an
+/// actor's next turn.
+#[derive(Default)]
+pub struct Scheduler {
+    energy: HashMap<Entity, i32>,
+    turn: Turn,
+}
+
+/// Whose turn it is.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Turn {
+    /// Waiting for the player to act.
+    #[default]
+    Player,
+    /// An NPC acting.
+    Actor(Entity),
+    /// The turn has been paid for, the next actor is yet to be picked.
+    Over,
+}
+
+impl Scheduler {
+    #[inline]
+    pub fn energy(&self, actor: Entity) -> i32 {
+        self.energy.get(&actor).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn set_energy(&mut self, actor: Entity, energy: i32) {
+        self.energy.insert(actor, energy);
+    }
+
+    /// Whether ending a turn of `actor` counts. Actions can end a turn more
+    /// than once, e.g. opening both doors of a gateway, but only the first one
+    /// is paid for. The player may always end their turn, even if an NPC never
+    /// finished theirs.
+    pub fn ends(&self, actor: Entity, player: bool) -> bool {
+        match self.turn {
+            Turn::Player => player,
+            Turn::Actor(it) => it == actor || player,
+            Turn::Over => false,
+        }
+    }
+
+    /// Ends the turn of `actor`, paying `cost` energy.
+    pub fn end(&mut self, actor: Entity, cost: u16) {
+        *self.energy.entry(actor).or_default() -= cost as i32;
+        self.turn = Turn::Over;
+    }
+
+    /// The next of `actors` to take a turn, along with the ticks to pass until
+    /// then. Ties go to the actor with the most energy left over, then to the
+    /// player, then to the oldest entity.
+    pub fn next(
+        &self,
+        actors: impl IntoIterator<Item = (Entity, Speed, bool)>,
+    ) -> Option<(Entity, u32)> {
+        actors
+            .into_iter()
+            .filter(|(_, speed, _)| speed.0 > 0)
+            .map(|(actor, speed, player)| {
+                let energy = self.energy(actor);
+                let speed = speed.0 as i32;
+                let ticks = ((TURN - energy).max(0) + speed - 1) / speed;
+                let left = energy + ticks * speed;
+
+                (actor, ticks, Reverse(left), !player)
+            })
+            .min_by_key(|(actor, ticks, left, player)| (*ticks, *left, *player, actor.id()))
+            .map(|(actor, ticks, ..)| (actor, ticks as u32))
+    }
+
+    /// Lets `ticks` pass for `actors`, and hands the turn to `next`.
+    pub fn start(
+        &mut self,
+        actors: impl IntoIterator<Item = (Entity, Speed)>,
+        ticks: u32,
+        next: Entity,
+        player: bool,
+    ) {
+        let actors: HashMap<_, _> = actors.into_iter().collect();
+
+        self.energy.retain(|actor, _| actors.contains_key(actor));
+
+        for (actor, speed) in actors {
+            *self.energy.entry(actor).or_default() += speed.0 as i32 * ticks as i32;
+        }
+
+        self.turn = if player {
+            Turn::Player
+        } else {
+            Turn::Actor(next)
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scheduler() {
+        let player = Entity::from_raw(0);
+        let fast = Entity::from_raw(1);
+        let slow = Entity::from_raw(2);
+        let actors = [
+            (player, Speed::NORMAL, true),
+            (fast, Speed(20), false),
+            (slow, Speed(5), false),
+        ];
+
+        let mut scheduler = Scheduler::default();
+        let mut turns = Vec::new();
+
+        for _ in 0..8 {
+            let (actor, ticks) = scheduler.next(actors).unwrap();
+            scheduler.start(
+                actors.map(|(actor, speed, _)| (actor, speed)),
+                ticks,
+                actor,
+                actor == player,
+            );
+            scheduler.end(actor, TURN as u16);
+            turns.push(actor);
+        }
+
+        // The fast actor acts twice as often as the player, the slow one half
+        // as often; the player goes first when tied
+        assert_eq!(
+            turns,
+            vec![fast, player, fast, fast, player, fast, slow, fast]
+        );
+    }
+}
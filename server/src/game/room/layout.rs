@@ -0,0 +1,88 @@
+use super::*;
+
+/// Placement of all spawned rooms in a single, global coordinate system.
+///
+/// Rooms keep using their own coordinates; a room's origin is the global
+/// position of its local (0, 0). Walls of adjacent rooms may share cells, but
+/// nothing else may, except for the gateway connecting two rooms.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Layout {
+    origins: BTreeMap<RoomId, IVec2>,
+    cells: HashSet<IVec2>,
+    walls: HashSet<IVec2>,
+}
+
+impl Layout {
+    pub fn origin(&self, room: &RoomId) -> Option<IVec2> {
+        self.origins.get(room).copied()
+    }
+
+    /// Whether `room`, placed at `origin`, only overlaps spawned rooms at
+    /// shared walls and `gateway`.
+    pub fn fits(&self, room: &Room, origin: IVec2, gateway: IVec2) -> bool {
+        room.positions.iter().all(|(id, pos)| {
+            let cell = origin + *pos;
+
+            cell == gateway
+                || !self.cells.contains(&cell)
+                || (self.walls.contains(&cell)
+                    && matches!(room.tiles.get(id), Some(Tile::Wall { .. })))
+        })
+    }
+
+    pub fn insert(&mut self, id: RoomId, room: &Room, origin: IVec2) {
+        self.origins.insert(id, origin);
+
+        for (id, pos) in room.positions.iter() {
+            let cell = origin + *pos;
+
+            // A cell stays a wall only as long as every room has a wall there
+            let wall = matches!(room.tiles.get(id), Some(Tile::Wall { .. }))
+                && (!self.cells.contains(&cell) || self.walls.contains(&cell));
+
+            if wall {
+                self.walls.insert(cell);
+            } else {
+                self.walls.remove(&cell);
+            }
+
+            self.cells.insert(cell);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::ivec2;
+
+    use super::*;
+
+    #[test]
+    fn test_fits() {
+        let base: Room = "
+#####
+#···#
+#···-
+#####"
+            .parse()
+            .unwrap();
+        let room: Room = "
+###
+|·#
+###"
+        .parse()
+        .unwrap();
+
+        let mut layout = Layout::default();
+        layout.insert(RoomId(0), &base, ivec2(0, 0));
+
+        // Sharing the gateway and the walls next to it
+        assert!(layout.fits(&room, ivec2(4, 0), ivec2(4, 1)));
+        // Running into the base's floor
+        assert!(!layout.fits(&room, ivec2(3, 0), ivec2(4, 1)));
+        // Overlapping the base's spawner
+        assert!(!layout.fits(&room, ivec2(4, 1), ivec2(4, 2)));
+        // Far away
+        assert!(layout.fits(&room, ivec2(10, 10), ivec2(4, 1)));
+    }
+}
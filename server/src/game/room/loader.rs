@@ -1,79 +1,247 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
+//! Parsing of room assets.
+//!
+//! A room asset is an ASCII map of the room, optionally preceded by a TOML
+//! header between two `+++` lines:
+//!
+//! ```text
+//! +++
+//! door = "storage"
+//!
+//! [legend]
+//! x = { tile = "wall", switch = ["cache"] }
+//! 1 = { tile = "door", lock = "cache" }
+//!
+//! [locks.cache]
+//! active = true
+//! +++
+//! #######
+//! #··x··#
+//! ```
+//!
+//! The legend maps characters of the map to tiles, on top of the default
+//! legend below. `door` is the kind of all doors not naming one themselves,
+//! `generate = false` keeps a room out of random generation. Doors naming the
+//! same lock are locked together, and a switch toggles the locks it names along
+//! with their doors.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 
-use bevy_ecs::prelude::*;
 use glam::ivec2;
+use serde::Deserialize;
+use thiserror::Error;
 
-use crate::game::component::Direction;
+use crate::game::component::{Direction, DoorKind};
 use crate::game::resource::Deltas;
 
 use super::*;
 
-const ROOM_ASSET_PREFIX: &'static str = "assets/rooms/";
+const ROOM_ASSET_PREFIX: &str = "assets/rooms/";
+const ROOM_ASSET_EXTENSION: &str = "room";
+const HEADER_DELIMITER: &str = "+++";
 
-pub fn load_asset(name: &str) -> String {
-    let path = Path::new(ROOM_ASSET_PREFIX).join(name);
-    fs::read_to_string(path)
-        .expect("asset can be loaded as string")
-        .into()
+#[derive(Debug, Error)]
+pub enum RoomError {
+    #[error("Room header is not terminated by +++")]
+    UnterminatedHeader,
+    #[error("Invalid room header: {0}")]
+    Header(#[from] toml::de::Error),
+    #[error("Unknown room char {0:?} in map line {1}")]
+    Char(char, usize),
+    #[error("Unknown lock {0:?}")]
+    Lock(String),
 }
 
-pub trait RoomLoader: 'static {
-    fn source() -> String;
-
-    fn char_tile(c: char) -> Option<Tile> {
-        match c {
-            '@' => Some(Tile::Player),
-            'b' => Some(Tile::NPC(NPC::ServBot)),
-            'c' => Some(Tile::Object(Object::Server)),
-            '#' => Some(Tile::Wall),
-            'O' => Some(Tile::Door(Door::Open)),
-            'o' => Some(Tile::Door(Door::Closed)),
-            '-' | '|' => Some(Tile::Door(Door::Spawner)),
-            '·' => Some(Tile::Floor),
-            ' ' => None,
-            _ => {
-                log::error!("Unknown room char {}", c);
-                None
-            }
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct Header {
+    generate: bool,
+    door: DoorKind,
+    legend: HashMap<char, LegendTile>,
+    locks: BTreeMap<String, LockGroup>,
+}
+
+/// A `Tile` as written in the legend, named by its `tile` key.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "tile", rename_all = "kebab-case")]
+enum LegendTile {
+    Floor,
+    Wall {
+        #[serde(default)]
+        switch: Vec<String>,
+    },
+    Door {
+        #[serde(default)]
+        state: Door,
+        kind: Option<DoorKind>,
+        lock: Option<String>,
+    },
+    Player,
+    #[serde(rename = "npc")]
+    NPC {
+        npc: NPC,
+    },
+    Object {
+        object: Object,
+    },
+    Item {
+        item: Item,
+    },
+}
+
+impl From<LegendTile> for Tile {
+    fn from(tile: LegendTile) -> Self {
+        match tile {
+            LegendTile::Floor => Tile::Floor,
+            LegendTile::Wall { switch } => Tile::Wall { switch },
+            LegendTile::Door { state, kind, lock } => Tile::Door { state, kind, lock },
+            LegendTile::Player => Tile::Player,
+            LegendTile::NPC { npc } => Tile::NPC { npc },
+            LegendTile::Object { object } => Tile::Object { object },
+            LegendTile::Item { item } => Tile::Item { item },
+        }
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            generate: true,
+            door: DoorKind::Heavy,
+            legend: Default::default(),
+            locks: Default::default(),
+        }
+    }
+}
+
+fn default_legend() -> HashMap<char, Tile> {
+    let door = |state| Tile::Door {
+        state,
+        kind: None,
+        lock: None,
+    };
+
+    [
+        ('·', Tile::Floor),
+        ('#', Tile::Wall { switch: Vec::new() }),
+        ('O', door(Door::Open)),
+        ('o', door(Door::Closed)),
+        ('-', door(Door::Spawner)),
+        ('|', door(Door::Spawner)),
+        ('@', Tile::Player),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Loads all room assets, by file name without extension.
+pub fn load_assets() -> BTreeMap<String, Room> {
+    let entries = fs::read_dir(ROOM_ASSET_PREFIX).expect("room asset directory can be read");
+
+    entries
+        .map(|entry| entry.expect("room asset directory can be read").path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(ROOM_ASSET_EXTENSION))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            (name, load_asset(&path))
+        })
+        .collect()
+}
+
+fn load_asset(path: &Path) -> Room {
+    let source = fs::read_to_string(path).expect("asset can be loaded as string");
+
+    match source.parse() {
+        Ok(room) => room,
+        Err(e) => panic!("Invalid room asset {}: {}", path.display(), e),
+    }
+}
+
+/// Splits `s` into its header, if any, and the map.
+fn split_header(s: &str) -> Result<(Option<&str>, &str), RoomError> {
+    let mut lines = s.split_inclusive('\n');
+
+    if lines.next().map(str::trim_end) != Some(HEADER_DELIMITER) {
+        return Ok((None, s));
+    }
+
+    let start = s.find('\n').map(|i| i + 1).unwrap_or(s.len());
+    let mut end = start;
+
+    for line in lines {
+        if line.trim_end() == HEADER_DELIMITER {
+            return Ok((Some(&s[start..end]), &s[end + line.len()..]));
         }
+        end += line.len();
     }
 
-    fn load() -> Room {
-        let mut index = 0;
-        let mut positions = HashMap::new();
-        let mut tiles = HashMap::new();
-        let mut chars = HashMap::new();
+    Err(RoomError::UnterminatedHeader)
+}
+
+impl FromStr for Room {
+    type Err = RoomError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (header, source) = split_header(s)?;
+        let header: Header = match header {
+            Some(header) => toml::from_str(header)?,
+            None => Default::default(),
+        };
+
+        let mut legend = default_legend();
+        legend.extend(header.legend.into_iter().map(|(c, tile)| (c, tile.into())));
+
+        let mut index: RoomEntity = 0;
+        let mut positions = BTreeMap::new();
+        let mut tiles = BTreeMap::new();
         let mut player = None;
-        let mut spawners = HashSet::new();
-        let mut walls = HashSet::new();
+        let mut spawners = BTreeSet::new();
         let mut doors = HashMap::new();
-        let mut bulkhead_doors = HashMap::new();
+        let mut bulkhead_doors = BTreeMap::new();
         let mut width = 0;
         let mut height = 0;
 
-        for (y, line) in Self::source().lines().rev().enumerate() {
+        let lines: Vec<_> = source.lines().collect();
+
+        for (y, line) in lines.iter().rev().enumerate() {
             for (x, c) in line.chars().enumerate() {
-                let tile = match Self::char_tile(c) {
-                    Some(tile) => tile,
-                    None => {
-                        continue;
-                    }
-                };
+                if c == ' ' {
+                    continue;
+                }
+
+                let tile = legend
+                    .get(&c)
+                    .cloned()
+                    .ok_or(RoomError::Char(c, lines.len() - y))?;
 
                 let pos = ivec2(x as i32, y as i32);
                 positions.insert(index, pos);
 
-                match tile {
-                    Tile::Wall => {
-                        walls.insert(index);
-                    }
-                    Tile::Door(Door::Spawner) => {
-                        doors.insert(pos, index);
-                        spawners.insert(index);
+                match &tile {
+                    Tile::Wall { switch } => {
+                        let unknown = switch.iter().find(|it| !header.locks.contains_key(*it));
+                        if let Some(lock) = unknown {
+                            return Err(RoomError::Lock(lock.clone()));
+                        }
                     }
-                    Tile::Door(_) => {
-                        doors.insert(pos, index);
+                    Tile::Door { state, kind, lock } => {
+                        if let Some(lock) = lock
+                            && !header.locks.contains_key(lock)
+                        {
+                            return Err(RoomError::Lock(lock.clone()));
+                        }
+
+                        match state {
+                            Door::Spawner => {
+                                spawners.insert(index);
+                            }
+                            _ if matches!(kind.unwrap_or(header.door), DoorKind::Bulkhead) => {
+                                doors.insert(pos, index);
+                            }
+                            _ => {}
+                        }
                     }
                     Tile::Player => {
                         player = Some(index);
@@ -82,7 +250,6 @@ pub trait RoomLoader: 'static {
                 }
 
                 tiles.insert(index, tile);
-                chars.insert(index, c);
 
                 width = width.max(x + 1);
                 height = height.max(y + 1);
@@ -92,7 +259,7 @@ pub trait RoomLoader: 'static {
 
         let deltas = Deltas::cross();
 
-        let spawners: HashMap<RoomEntity, Direction> = {
+        let spawners: BTreeMap<RoomEntity, Direction> = {
             let occupied: HashSet<_> = positions.values().collect();
 
             spawners
@@ -117,26 +284,23 @@ pub trait RoomLoader: 'static {
                 .collect()
         };
 
-        for (pos, id) in doors.clone() {
-            if let Some(other_id) = deltas.0.iter().find_map(|d| doors.get(&(pos + *d))) {
-                bulkhead_doors.insert(id, *other_id);
+        for (pos, id) in doors.iter() {
+            if let Some(other_id) = deltas.0.iter().find_map(|d| doors.get(&(*pos + *d))) {
+                bulkhead_doors.insert(*id, *other_id);
             }
         }
 
-        Room {
-            index,
+        Ok(Room {
             positions,
             tiles,
-            chars,
             player,
             spawners,
-            walls,
             bulkhead_doors,
             width: width as u32,
             height: height as u32,
-            loader: Arc::new(Self::spawn),
-        }
+            generate: header.generate,
+            door: header.door,
+            locks: header.locks,
+        })
     }
-
-    fn spawn(room: &Room, room_id: RoomId, commands: &mut Commands);
 }
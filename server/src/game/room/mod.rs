@@ -1,38 +1,26 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
-use std::fs;
-use std::path::Path;
-use std::sync::Arc;
 
 use bevy_ecs::prelude::*;
 use bevy_hierarchy::BuildChildren;
 use glam::{ivec2, IVec2};
 use rand::prelude::*;
-use strum::{EnumIter, IntoEnumIterator};
+use serde::{Deserialize, Serialize};
 
 use crate::game::bundle;
 use crate::game::component;
 
-use self::cyberspace_cache::CyberspaceCacheRoom;
-use self::floor_medical::FloorMedicalRoom;
-use self::hibernation::HibernationRoom;
-use self::loader::RoomLoader;
-use self::medical_bay::MedicalBayRoom;
-use self::server::ServerRoom;
-use self::storage::StorageRoom;
+use super::component::{Direction, DoorKind};
 
-use super::component::Direction;
+pub use self::layout::Layout;
 
-mod cyberspace_cache;
-mod floor_medical;
-mod hibernation;
+mod layout;
 mod loader;
-mod medical_bay;
-mod server;
-mod storage;
+mod spawn;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
 pub struct RoomId(pub u16);
 
 impl RoomId {
@@ -41,57 +29,40 @@ impl RoomId {
     }
 }
 
-pub struct Rooms(HashMap<RoomAsset, Room>);
+/// All room assets, by name.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rooms(BTreeMap<String, Room>);
 
 impl Default for Rooms {
     fn default() -> Self {
-        let assets = RoomAsset::iter()
-            .map(|asset| (asset, asset.load()))
-            .collect();
+        Self(loader::load_assets())
+    }
+}
 
-        Self(assets)
+impl FromIterator<(String, Room)> for Rooms {
+    fn from_iter<T: IntoIterator<Item = (String, Room)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
     }
 }
 
 impl Rooms {
-    #[allow(dead_code)]
-    pub fn get<'a>(&'a self, asset: &RoomAsset) -> &'a Room {
-        self.0.get(asset).unwrap()
+    pub fn get<'a>(&'a self, name: &str) -> Option<&'a Room> {
+        self.0.get(name)
     }
 
-    pub fn random<P, R>(&self, rng: &mut R, predicate: P) -> Option<Room>
+    /// All rooms for random generation fulfilling `predicate`, in random order.
+    pub fn candidates<P, R>(&self, rng: &mut R, predicate: P) -> Vec<&Room>
     where
         P: Fn(&Room) -> bool,
         R: Rng + ?Sized,
     {
-        self.0
+        let mut rooms: Vec<_> = self
+            .0
             .values()
-            .filter(|room| predicate(*room))
-            .choose(rng)
-            .cloned()
-    }
-}
-
-#[derive(Hash, PartialEq, Eq, Clone, Copy, EnumIter)]
-pub enum RoomAsset {
-    Hibernation,
-    MedicalBay,
-    FloorMedical,
-    Storage,
-    CyberspaceCache,
-    Server,
-}
-
-impl RoomAsset {
-    pub fn load(&self) -> Room {
-        match self {
-            RoomAsset::Hibernation => HibernationRoom::load(),
-            RoomAsset::MedicalBay => MedicalBayRoom::load(),
-            RoomAsset::FloorMedical => FloorMedicalRoom::load(),
-            RoomAsset::Storage => StorageRoom::load(),
-            RoomAsset::CyberspaceCache => CyberspaceCacheRoom::load(),
-            RoomAsset::Server => ServerRoom::load(),
-        }
+            .filter(|room| room.generate && predicate(*room))
+            .collect();
+        rooms.shuffle(rng);
+        rooms
     }
 }
 
@@ -109,65 +80,86 @@ pub struct Mutation {
     mirror: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Tile {
     Floor,
-    Wall,
-    Door(Door),
+    Wall {
+        /// Locks toggled by a switch on this wall
+        switch: Vec<String>,
+    },
+    Door {
+        state: Door,
+        kind: Option<DoorKind>,
+        lock: Option<String>,
+    },
     Player,
-    NPC(NPC),
-    Object(Object),
+    NPC {
+        npc: NPC,
+    },
+    Object {
+        object: Object,
+    },
+    Item {
+        item: Item,
+    },
 }
 
 type RoomEntity = u16;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Door {
     Open,
+    #[default]
     Closed,
     Spawner,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum NPC {
     ServBot,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Object {
     Server,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Item {
+    LeadPipe,
+    AssaultRifle,
+    IonRifle,
+    MagnesiumTips,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockGroup {
+    active: bool,
+}
+
+impl Default for LockGroup {
+    fn default() -> Self {
+        Self { active: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
-    index: RoomEntity,
-    positions: HashMap<RoomEntity, IVec2>,
-    tiles: HashMap<RoomEntity, Tile>,
-    chars: HashMap<RoomEntity, char>,
+    positions: BTreeMap<RoomEntity, IVec2>,
+    tiles: BTreeMap<RoomEntity, Tile>,
     player: Option<RoomEntity>,
-    spawners: HashMap<RoomEntity, Direction>,
-    walls: HashSet<RoomEntity>,
-    bulkhead_doors: HashMap<RoomEntity, RoomEntity>,
+    spawners: BTreeMap<RoomEntity, Direction>,
+    bulkhead_doors: BTreeMap<RoomEntity, RoomEntity>,
     width: u32,
     height: u32,
-    loader: Arc<dyn Fn(&Room, RoomId, &mut Commands) -> () + Send + Sync>,
-}
-
-impl core::fmt::Debug for Room {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Room")
-            .field("index", &self.index)
-            .field("positions", &self.positions)
-            .field("tiles", &self.tiles)
-            .field("chars", &self.chars)
-            .field("player", &self.player)
-            .field("spawners", &self.spawners)
-            .field("walls", &self.walls)
-            .field("bulkhead_doors", &self.bulkhead_doors)
-            .field("width", &self.width)
-            .field("height", &self.height)
-            .finish()
-    }
+    generate: bool,
+    door: DoorKind,
+    locks: BTreeMap<String, LockGroup>,
 }
 
 impl Display for Room {
@@ -181,19 +173,29 @@ impl Display for Room {
                     .get(&ivec2(x, y))
                     .and_then(|id| self.tiles.get(id).map(|tile| (tile, id)))
                 {
-                    Some((&Tile::Floor, _)) => '·',
-                    Some((&Tile::Wall, _)) => '#',
-                    Some((&Tile::Door(Door::Open), _)) => 'O',
-                    Some((&Tile::Door(Door::Closed), _)) => 'o',
-                    Some((&Tile::Door(Door::Spawner), id)) => {
-                        match self.spawners.get(id).unwrap() {
+                    Some((Tile::Floor, _)) => '·',
+                    Some((Tile::Wall { .. }, _)) => '#',
+                    Some((Tile::Door { state, .. }, id)) => match state {
+                        Door::Open => 'O',
+                        Door::Closed => 'o',
+                        Door::Spawner => match self.spawners.get(id).unwrap() {
                             Direction::North | Direction::South => '-',
                             Direction::East | Direction::West => '|',
-                        }
-                    }
-                    Some((&Tile::Player, _)) => '@',
-                    Some((&Tile::NPC(NPC::ServBot), _)) => 'b',
-                    Some((&Tile::Object(Object::Server), _)) => 'c',
+                        },
+                    },
+                    Some((Tile::Player, _)) => '@',
+                    Some((Tile::NPC { npc }, _)) => match npc {
+                        NPC::ServBot => 'b',
+                    },
+                    Some((Tile::Object { object }, _)) => match object {
+                        Object::Server => 'c',
+                    },
+                    Some((Tile::Item { item }, _)) => match item {
+                        Item::LeadPipe => '/',
+                        Item::AssaultRifle => '}',
+                        Item::IonRifle => '{',
+                        Item::MagnesiumTips => '=',
+                    },
                     None => ' ',
                 };
 
@@ -223,19 +225,32 @@ impl Room {
     }
 
     pub fn release_spawner(&mut self, entity: &RoomEntity) {
-        *self.tiles.get_mut(entity).unwrap() = Tile::Door(Door::Open);
+        if let Some(Tile::Door { state, .. }) = self.tiles.get_mut(entity) {
+            *state = Door::Open;
+        }
     }
 
-    pub fn random_spawner<'a, R>(&'a self, rng: &mut R) -> Option<&'a RoomEntity>
+    /// All spawners, in random order.
+    pub fn spawners<R>(&self, rng: &mut R) -> Vec<RoomEntity>
     where
         R: Rng + ?Sized,
     {
-        self.spawners.keys().choose(rng)
+        let mut spawners: Vec<_> = self.spawners.keys().copied().collect();
+        spawners.shuffle(rng);
+        spawners
     }
 
-    pub fn turn_towards(&mut self, spawner: &RoomEntity, direction: &Direction) -> Result<(), ()> {
-        let from = self.spawners.get(spawner).ok_or(())?;
-        let diff = *direction as i8 - *from as i8;
+    /// Rotates the room so that `spawner` faces `direction`, after mirroring
+    /// it if `mirror` is set.
+    pub fn turn_towards(
+        &mut self,
+        spawner: &RoomEntity,
+        direction: &Direction,
+        mirror: bool,
+    ) -> Result<(), ()> {
+        let from = *self.spawners.get(spawner).ok_or(())?;
+        let from = if mirror { from.mirror() } else { from };
+        let diff = *direction as i8 - from as i8;
 
         let rotate = match diff {
             0 => Rotate::Zero,
@@ -245,18 +260,11 @@ impl Room {
             _ => panic!("Impossible"),
         };
 
-        self.mutate(&Mutation {
-            rotate,
-            mirror: false,
-        });
+        self.mutate(&Mutation { rotate, mirror });
 
         Ok(())
     }
 
-    pub fn spawn(&self, room_id: RoomId, commands: &mut Commands) {
-        (self.loader)(self, room_id, commands)
-    }
-
     fn mutate(&mut self, mutation: &Mutation) {
         let Mutation { mirror, rotate } = mutation;
 
@@ -281,7 +289,13 @@ impl Room {
         }
 
         for (_, direction) in self.spawners.iter_mut() {
-            match (*direction as usize + *rotate as usize) % 4 {
+            let from = if *mirror {
+                direction.mirror()
+            } else {
+                *direction
+            };
+
+            match (from as usize + *rotate as usize) % 4 {
                 0 => *direction = Direction::North,
                 1 => *direction = Direction::East,
                 2 => *direction = Direction::South,
@@ -297,300 +311,160 @@ impl Room {
     }
 }
 
-// #[cfg(test)]
-// mod test {
-//     use test_log::test;
-
-//     use super::*;
-
-//     #[test]
-//     fn test_room_rotation() {
-//         let room: Room = "#@######
-// ########
-// ########
-// "
-//         .to_string()
-//         .into();
-
-//         assert_eq!(
-//             room.to_string(),
-//             "#@######
-// ########
-// ########
-// "
-//             .to_string(),
-//         );
-
-//         let mut mutation = Mutation {
-//             mirror: false,
-//             rotate: Rotate::Ninety,
-//         };
-
-//         let ninety = room.mutate(&mutation);
-
-//         assert_eq!(
-//             ninety.to_string(),
-//             "###
-// ##@
-// ###
-// ###
-// ###
-// ###
-// ###
-// ###
-// "
-//             .to_string(),
-//         );
-
-//         mutation.rotate = Rotate::OneHundredEighty;
-
-//         let oneeighty = room.mutate(&mutation);
-
-//         assert_eq!(
-//             oneeighty.to_string(),
-//             "########
-// ########
-// ######@#
-// "
-//             .to_string(),
-//         );
-
-//         mutation.rotate = Rotate::TwoHundredSeventy;
-
-//         let twoseventy = room.mutate(&mutation);
-
-//         assert_eq!(
-//             twoseventy.to_string(),
-//             "###
-// ###
-// ###
-// ###
-// ###
-// ###
-// @##
-// ###
-// "
-//             .to_string(),
-//         );
-//     }
-
-//     #[test]
-//     fn test_room_mirroring() {
-//         let room: Room = "#@######
-// ########
-// ########
-// "
-//         .to_string()
-//         .into();
-
-//         let mut mutation = Mutation {
-//             mirror: true,
-//             rotate: Rotate::Zero,
-//         };
-
-//         let mirror = room.mutate(&mutation);
-
-//         assert_eq!(
-//             mirror.to_string(),
-//             "######@#
-// ########
-// ########
-// "
-//             .to_string(),
-//         );
-
-//         mutation.rotate = Rotate::Ninety;
-
-//         let ninety = room.mutate(&mutation);
-
-//         assert_eq!(
-//             ninety.to_string(),
-//             "###
-// ###
-// ###
-// ###
-// ###
-// ###
-// ##@
-// ###
-// "
-//             .to_string(),
-//         );
-
-//         mutation.rotate = Rotate::OneHundredEighty;
-
-//         let oneeighty = room.mutate(&mutation);
-
-//         assert_eq!(
-//             oneeighty.to_string(),
-//             "########
-// ########
-// #@######
-// "
-//             .to_string(),
-//         );
-
-//         mutation.rotate = Rotate::TwoHundredSeventy;
-
-//         let twoseventy = room.mutate(&mutation);
-
-//         assert_eq!(
-//             twoseventy.to_string(),
-//             "###
-// @##
-// ###
-// ###
-// ###
-// ###
-// ###
-// ###
-// "
-//             .to_string(),
-//         );
-//     }
-
-//     #[test]
-//     fn test_building_trivial() {
-//         let base: Room = "
-//   ######
-// #-#··###
-// #······#
-// #·····@#
-// #o#··###
-// #·######
-// ###
-// "
-//         .to_string()
-//         .into();
-
-//         let mut spatial: SpatialHash = Default::default();
-//         base.spatial_merge(&mut spatial);
-//         let find = FindSite::new(spatial.clone());
-
-//         let room: Room = "
-// #########
-// #··#····#
-// #·b·····#
-// #·····#·#
-// #·#·#·#-#
-// #·###·#
-// #··#··#
-// #····##
-// |b·#·##
-// #··#··##
-// #####··#
-//    #####
-// "
-//         .to_string()
-//         .into();
-
-//         let room = find.find_site(&room, (&ivec2(1, 5), &ivec2(1, 6))).unwrap();
-
-//         room.spatial_merge(&mut spatial);
-
-//         let expected = "#########
-// #··#····#
-// #·······#
-// #·····#·######
-// #·#·#·#o#··###
-// #·###·#······#
-// #··#··#······#
-// #····##o#··###
-// o··#·##·######
-// #··#··###
-// #####··#
-//    #####
-// ";
-
-//         assert_eq!(spatial.to_string(), expected);
-//     }
-
-//     #[test]
-//     fn test_building_offset() {
-//         let base: Room = "
-// #########
-// #··#····#
-// #·b·····#
-// #·····#·#
-// #·#·#·#-#
-// #·###·#
-// #··#··#
-// #····##
-// |b·#·##
-// #··#··##
-// #####··#
-//    #####
-// "
-//         .to_string()
-//         .into();
-
-//         let mut spatial: SpatialHash = Default::default();
-//         base.spatial_merge(&mut spatial);
-//         let find = FindSite::new(spatial.clone());
-
-//         let room: Room = "
-//   ######
-// #-#··###
-// #······#
-// #·····@#
-// #o#··###
-// #·######
-// ###
-// "
-//         .to_string()
-//         .into();
-
-//         // let room = find
-//         //     .find_site(&room, (&ivec2(0, 3), &ivec2(-1, 3)))
-//         //     .unwrap();
-
-//         // let doors = [ivec2(-1, 8)].into_iter().collect();
-//         // assert_eq!(room.doors, doors);
-//     }
-
-//     #[test]
-//     fn test_building_greater_offset() {
-//         let base: Room = "
-// #########
-// #··#····#
-// #·b·····#
-// #·····#·#
-// #·#·#·#-#
-// #·###·#
-// #··#··#
-// #····##
-// |b·#·##
-// #··#··##
-// #####··#
-//    #####
-// "
-//         .to_string()
-//         .into();
-
-//         let mut spatial: SpatialHash = Default::default();
-//         base.spatial_merge(&mut spatial);
-//         let find = FindSite::new(spatial.clone());
-
-//         // println!("{:?}", spatial.cells.get(&ivec2(-1, 3)));
-
-//         let room: Room = "
-//   ######
-//   #··###
-// #-#···##
-// #······#
-// #·····@#
-// #o#··###
-// #·######
-// ###
-// "
-//         .to_string()
-//         .into();
-
-//         let room = find
-//             .find_site(&room, (&ivec2(0, 3), &ivec2(-1, 3)))
-//             .unwrap();
-
-//         // let doors = [ivec2(-1, 8)].into_iter().collect();
-//         // assert_eq!(room.doors, doors);
-//     }
-// }
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    fn room(s: &str) -> Room {
+        s.parse().unwrap()
+    }
+
+    fn mutated(room: &Room, rotate: Rotate, mirror: bool) -> String {
+        let mut room = room.clone();
+        room.mutate(&Mutation { rotate, mirror });
+        room.to_string()
+    }
+
+    fn turned(room: &Room, spawner: RoomEntity, direction: Direction) -> Room {
+        let mut room = room.clone();
+        room.turn_towards(&spawner, &direction, true).unwrap();
+        room
+    }
+
+    #[test]
+    fn test_room_rotation() {
+        let room = room(
+            "#@######
+########
+########
+",
+        );
+
+        assert_eq!(
+            room.to_string(),
+            "#@######
+########
+########
+"
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::Ninety, false),
+            "###
+##@
+###
+###
+###
+###
+###
+###
+"
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::OneHundredEighty, false),
+            "########
+########
+######@#
+"
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::TwoHundredSeventy, false),
+            "###
+###
+###
+###
+###
+###
+@##
+###
+"
+        );
+    }
+
+    #[test]
+    fn test_room_mirroring() {
+        let room = room(
+            "#@######
+########
+########
+",
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::Zero, true),
+            "######@#
+########
+########
+"
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::Ninety, true),
+            "###
+###
+###
+###
+###
+###
+##@
+###
+"
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::OneHundredEighty, true),
+            "########
+########
+#@######
+"
+        );
+
+        assert_eq!(
+            mutated(&room, Rotate::TwoHundredSeventy, true),
+            "###
+@##
+###
+###
+###
+###
+###
+###
+"
+        );
+    }
+
+    #[test]
+    fn test_turn_towards_mirrored() {
+        let base = room(
+            "###
+|·#
+###
+",
+        );
+        let spawner = *base.spawners.keys().next().unwrap();
+
+        let room = turned(&base, spawner, Direction::East);
+        assert_eq!(room.spawners.get(&spawner), Some(&Direction::East));
+        assert_eq!(
+            room.to_string(),
+            "###
+#·|
+###
+"
+        );
+
+        let room = turned(&base, spawner, Direction::North);
+        assert_eq!(room.spawners.get(&spawner), Some(&Direction::North));
+        assert_eq!(
+            room.to_string(),
+            "#-#
+#·#
+###
+"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use super::*;
+
+impl Room {
+    pub fn spawn(&self, room_id: RoomId, commands: &mut Commands) {
+        let locks: BTreeMap<&str, Entity> = self
+            .locks
+            .keys()
+            .map(|name| (name.as_str(), commands.spawn().id()))
+            .collect();
+        let mut locked: BTreeMap<&str, Vec<Entity>> = BTreeMap::new();
+        let mut switches = Vec::new();
+
+        let mut bulkhead_doors: BTreeMap<RoomEntity, Entity> = BTreeMap::new();
+        for (left, right) in self.bulkhead_doors.iter() {
+            let door = match bulkhead_doors
+                .get(left)
+                .or_else(|| bulkhead_doors.get(right))
+            {
+                Some(door) => *door,
+                None => commands
+                    .spawn()
+                    .insert(component::Description {
+                        name: "bulkhead door".to_string(),
+                        article: component::Article::A,
+                    })
+                    .id(),
+            };
+            bulkhead_doors.insert(*left, door);
+            bulkhead_doors.insert(*right, door);
+        }
+
+        for (id, pos) in self.positions.iter() {
+            let position = component::Position {
+                coordinates: *pos,
+                room: room_id,
+            };
+
+            commands.spawn().insert_bundle(bundle::Floor {
+                position: position.clone(),
+                ..Default::default()
+            });
+
+            match self.tiles.get(id).unwrap() {
+                Tile::Floor => {}
+
+                Tile::Wall { switch } => {
+                    commands.spawn().insert_bundle(bundle::Wall {
+                        position: position.clone(),
+                        ..Default::default()
+                    });
+
+                    if !switch.is_empty() {
+                        let entity = spawn_switch(position, commands);
+                        switches.push((entity, switch));
+                    }
+                }
+
+                Tile::Door { state, kind, lock } => {
+                    let door = commands
+                        .spawn()
+                        .insert_bundle(bundle::Door {
+                            position,
+                            ..Default::default()
+                        })
+                        .id();
+
+                    let mut door = match bulkhead_doors.get(id) {
+                        Some(entity) => {
+                            let mut entity = commands.entity(*entity);
+                            entity.add_child(door);
+                            entity
+                        }
+                        None => commands.entity(door),
+                    };
+
+                    door.insert(kind.unwrap_or(self.door));
+
+                    match state {
+                        Door::Open => {
+                            door.insert(component::Door { open: true });
+                        }
+                        Door::Closed => {
+                            door.insert(component::Door { open: false })
+                                .insert(component::Solid)
+                                .insert(component::Opaque);
+                        }
+                        Door::Spawner => {
+                            door.insert(component::Door { open: false })
+                                .insert(component::Solid)
+                                .insert(component::Opaque)
+                                .insert(component::RoomSpawner);
+                        }
+                    }
+
+                    if let Some(lock) = lock {
+                        let doors = locked.entry(lock.as_str()).or_default();
+                        if !doors.contains(&door.id()) {
+                            doors.push(door.id());
+                        }
+                    }
+                }
+
+                Tile::Player => spawn_player(position, commands),
+
+                Tile::NPC { npc } => spawn_npc(*npc, position, commands),
+
+                Tile::Object { object } => spawn_object(*object, position, commands),
+
+                Tile::Item { item } => spawn_item(*item, position, commands),
+            }
+        }
+
+        for (switch, names) in switches {
+            let targets = names
+                .iter()
+                .flat_map(|name| {
+                    let doors = locked.get(name.as_str()).into_iter().flatten();
+                    std::iter::once(locks[name.as_str()]).chain(doors.copied())
+                })
+                .collect();
+
+            commands
+                .entity(switch)
+                .insert(component::Switch { targets });
+        }
+
+        for (name, lock) in locks {
+            commands.entity(lock).insert(component::Lock {
+                active: self.locks[name].active,
+                locked: locked
+                    .remove(name)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+            });
+        }
+    }
+}
+
+fn spawn_switch(position: component::Position, commands: &mut Commands) -> Entity {
+    commands
+        .spawn()
+        .insert_bundle(bundle::Switch {
+            position,
+            renderable: component::Renderable::WallSwitch,
+            switch: component::Switch {
+                targets: Vec::new(),
+            },
+            description: component::Description {
+                name: "wall switch".to_string(),
+                article: component::Article::A,
+            },
+        })
+        .id()
+}
+
+fn spawn_player(position: component::Position, commands: &mut Commands) {
+    let player = commands
+        .spawn()
+        .insert_bundle(bundle::Player {
+            position,
+            ..Default::default()
+        })
+        .id();
+
+    commands
+        .spawn()
+        .insert_bundle(bundle::MeleeWeapon::laser_rapier())
+        .insert(component::Item {
+            owner: Some(player),
+        })
+        .insert(component::Equipped);
+
+    let rifle = commands
+        .spawn()
+        .insert_bundle(bundle::ProjectileGun::assault_rifle())
+        .insert(component::Item {
+            owner: Some(player),
+        })
+        .insert(component::Equipped)
+        .id();
+
+    for attached in [Some(rifle), None, None] {
+        let mut magazine = bundle::Magazine::magnesium_tips();
+        magazine.magazine.attached = attached;
+        magazine.item.owner = Some(player);
+        commands.spawn().insert_bundle(magazine);
+    }
+
+    commands
+        .spawn()
+        .insert_bundle(bundle::EnergyGun::ion_rifle())
+        .insert(component::Item {
+            owner: Some(player),
+        });
+}
+
+fn spawn_npc(npc: NPC, position: component::Position, commands: &mut Commands) {
+    match npc {
+        NPC::ServBot => {
+            let npc = commands
+                .spawn()
+                .insert_bundle(bundle::NPC {
+                    position,
+                    ai: component::AI::ServBot,
+                    renderable: component::Renderable::ServBot,
+                    sight: component::Sight {
+                        kind: component::SightKind::Eyes,
+                        ..Default::default()
+                    },
+                    description: component::Description {
+                        name: "Serv-Bot unit".into(),
+                        article: component::Article::A,
+                    },
+                    vulnerable: component::Vulnerable {
+                        kind: component::VulnerableKind::Robot,
+                        hp: 20,
+                        max: 20,
+                        defense: 2,
+                        armor: 20,
+                    },
+                    ..Default::default()
+                })
+                .insert(component::Alive::ServBot)
+                .id();
+
+            commands
+                .spawn()
+                .insert_bundle(bundle::NaturalMeleeWeapon::appendages())
+                .insert(component::Item { owner: Some(npc) })
+                .insert(component::Equipped);
+        }
+    }
+}
+
+fn spawn_object(object: Object, position: component::Position, commands: &mut Commands) {
+    match object {
+        Object::Server => {
+            commands
+                .spawn()
+                .insert_bundle(bundle::Object {
+                    renderable: component::Renderable::Server,
+                    position,
+                    description: component::Description {
+                        name: "server node".to_string(),
+                        article: component::Article::A,
+                    },
+                })
+                .insert(component::Solid)
+                .insert(component::Vulnerable {
+                    kind: component::VulnerableKind::Robot,
+                    hp: 50,
+                    max: 50,
+                    defense: 0,
+                    armor: 0,
+                })
+                .insert(component::Destructible::Server);
+        }
+    }
+}
+
+fn spawn_item(item: Item, position: component::Position, commands: &mut Commands) {
+    let mut entity = commands.spawn();
+
+    match item {
+        Item::LeadPipe => entity.insert_bundle(bundle::MeleeWeapon::lead_pipe()),
+        Item::AssaultRifle => entity.insert_bundle(bundle::ProjectileGun::assault_rifle()),
+        Item::IonRifle => entity.insert_bundle(bundle::EnergyGun::ion_rifle()),
+        Item::MagnesiumTips => entity.insert_bundle(bundle::Magazine::magnesium_tips()),
+    };
+
+    entity.insert(position);
+}
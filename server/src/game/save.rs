@@ -0,0 +1,541 @@
+//! Snapshots of a running game's `World`.
+//!
+//! Every entity is stored with all of its components and its energy in the
+//! `Scheduler`, together with the `Rooms`, `RoomId`, `Layout`, `Log` and
+//! `Random` resources. The `Rooms` catalogue is saved rather than reloaded
+//! from the room assets, so that rooms generated after loading don't depend
+//! on the assets at hand. Entity references are written as plain ids and
+//! remapped to fresh entities on load. `Sight`s are stored by their kind
+//! only; they're recomputed once the world is restored.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{BuildWorldChildren, Parent};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::component::*;
+use super::resource;
+use super::room::{Layout, RoomId, Rooms};
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Could not access save file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not serialize game: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Could not deserialize game: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    rooms: Rooms,
+    room_id: RoomId,
+    layout: Layout,
+    log: resource::Log,
+    random: resource::Random,
+    entities: Vec<SavedEntity>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedEntity {
+    id: u32,
+    components: Vec<SavedComponent>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedMemory {
+    entity: u32,
+    components: MemoryComponents,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SavedComponent {
+    Player(Player),
+    Description(Description),
+    Wall(Wall),
+    Floor(Floor),
+    God(God),
+    Position(Position),
+    DoorKind(DoorKind),
+    Door(Door),
+    Renderable(Renderable),
+    Sight(SightKind),
+    Memory(Vec<SavedMemory>),
+    AIMemory(AIMemory),
+    AI(AI),
+    Speed(Speed),
+    /// Energy of an actor, kept by the `Scheduler` rather than a component.
+    Energy(i32),
+    Opaque(Opaque),
+    Solid(Solid),
+    Item {
+        owner: Option<u32>,
+    },
+    Damage(Damage),
+    MeleeWeapon(MeleeWeapon),
+    Projectile(Projectile),
+    RangedWeapon(RangedWeapon),
+    ProjectileGun(ProjectileGun),
+    Magazine {
+        gun: ProjectileGunKind,
+        projectile: Projectile,
+        amount: u16,
+        attached: Option<u32>,
+    },
+    Beam(Beam),
+    EnergyGun(EnergyGun),
+    PowerSetting(PowerSetting),
+    PersonalBattery(PersonalBattery),
+    MeleeSlot(MeleeSlot),
+    GunSlot(GunSlot),
+    Equipped(Equipped),
+    Vulnerable(Vulnerable),
+    Alive(Alive),
+    Destructible(Destructible),
+    RoomSpawner(RoomSpawner),
+    Gateway {
+        twin: u32,
+        direction: Direction,
+    },
+    Lock {
+        active: bool,
+        locked: Vec<u32>,
+    },
+    Switch {
+        targets: Vec<u32>,
+    },
+    Parent(u32),
+}
+
+impl SaveGame {
+    pub fn capture(world: &mut World) -> Self {
+        let mut entities: Vec<_> = world.query::<Entity>().iter(world).collect();
+        entities.sort_by_key(|entity| entity.id());
+
+        let entities = entities
+            .into_iter()
+            .map(|entity| SavedEntity {
+                id: entity.id(),
+                components: components(world, entity),
+            })
+            .collect();
+
+        Self {
+            rooms: world.resource::<Rooms>().clone(),
+            room_id: *world.resource::<RoomId>(),
+            layout: world.resource::<Layout>().clone(),
+            log: world.resource::<resource::Log>().clone(),
+            random: world.resource::<resource::Random>().clone(),
+            entities,
+        }
+    }
+
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.random.seed()
+    }
+
+    /// Spawns all saved entities into `world` and sets the saved resources.
+    pub fn restore(self, world: &mut World) {
+        let mut ids: HashMap<u32, Entity> = self
+            .entities
+            .iter()
+            .map(|saved| (saved.id, world.spawn().id()))
+            .collect();
+
+        // References to entities that no longer exist, e.g. memories of
+        // something destroyed, still need a stable identity.
+        let mut entity = |world: &mut World, id: u32| -> Entity {
+            *ids.entry(id).or_insert_with(|| world.spawn().id())
+        };
+
+        let mut parents = Vec::new();
+
+        for saved in self.entities {
+            let target = entity(world, saved.id);
+
+            for component in saved.components {
+                match component {
+                    SavedComponent::Player(it) => insert(world, target, it),
+                    SavedComponent::Description(it) => insert(world, target, it),
+                    SavedComponent::Wall(it) => insert(world, target, it),
+                    SavedComponent::Floor(it) => insert(world, target, it),
+                    SavedComponent::God(it) => insert(world, target, it),
+                    SavedComponent::Position(it) => insert(world, target, it),
+                    SavedComponent::DoorKind(it) => insert(world, target, it),
+                    SavedComponent::Door(it) => insert(world, target, it),
+                    SavedComponent::Renderable(it) => insert(world, target, it),
+                    SavedComponent::Sight(kind) => insert(
+                        world,
+                        target,
+                        Sight {
+                            kind,
+                            ..Default::default()
+                        },
+                    ),
+                    SavedComponent::Memory(memories) => {
+                        let memory = memories
+                            .into_iter()
+                            .map(|memory| (entity(world, memory.entity), memory.components))
+                            .collect();
+                        insert(world, target, Memory(memory));
+                    }
+                    SavedComponent::AIMemory(it) => insert(world, target, it),
+                    SavedComponent::AI(it) => insert(world, target, it),
+                    SavedComponent::Speed(it) => insert(world, target, it),
+                    SavedComponent::Energy(energy) => world
+                        .resource_mut::<resource::Scheduler>()
+                        .set_energy(target, energy),
+                    SavedComponent::Opaque(it) => insert(world, target, it),
+                    SavedComponent::Solid(it) => insert(world, target, it),
+                    SavedComponent::Item { owner } => {
+                        let owner = owner.map(|id| entity(world, id));
+                        insert(world, target, Item { owner });
+                    }
+                    SavedComponent::Damage(it) => insert(world, target, it),
+                    SavedComponent::MeleeWeapon(it) => insert(world, target, it),
+                    SavedComponent::Projectile(it) => insert(world, target, it),
+                    SavedComponent::RangedWeapon(it) => insert(world, target, it),
+                    SavedComponent::ProjectileGun(it) => insert(world, target, it),
+                    SavedComponent::Magazine {
+                        gun,
+                        projectile,
+                        amount,
+                        attached,
+                    } => {
+                        let attached = attached.map(|id| entity(world, id));
+                        insert(
+                            world,
+                            target,
+                            Magazine {
+                                gun,
+                                projectile,
+                                amount,
+                                attached,
+                            },
+                        );
+                    }
+                    SavedComponent::Beam(it) => insert(world, target, it),
+                    SavedComponent::EnergyGun(it) => insert(world, target, it),
+                    SavedComponent::PowerSetting(it) => insert(world, target, it),
+                    SavedComponent::PersonalBattery(it) => insert(world, target, it),
+                    SavedComponent::MeleeSlot(it) => insert(world, target, it),
+                    SavedComponent::GunSlot(it) => insert(world, target, it),
+                    SavedComponent::Equipped(it) => insert(world, target, it),
+                    SavedComponent::Vulnerable(it) => insert(world, target, it),
+                    SavedComponent::Alive(it) => insert(world, target, it),
+                    SavedComponent::Destructible(it) => insert(world, target, it),
+                    SavedComponent::RoomSpawner(it) => insert(world, target, it),
+                    SavedComponent::Gateway { twin, direction } => {
+                        let twin = entity(world, twin);
+                        insert(world, target, Gateway { twin, direction });
+                    }
+                    SavedComponent::Lock { active, locked } => {
+                        let locked = locked.into_iter().map(|id| entity(world, id)).collect();
+                        insert(world, target, Lock { active, locked });
+                    }
+                    SavedComponent::Switch { targets } => {
+                        let targets = targets.into_iter().map(|id| entity(world, id)).collect();
+                        insert(world, target, Switch { targets });
+                    }
+                    SavedComponent::Parent(id) => {
+                        parents.push((entity(world, id), target));
+                    }
+                }
+            }
+        }
+
+        for (parent, child) in parents {
+            world.entity_mut(parent).push_children(&[child]);
+        }
+
+        world.insert_resource(self.rooms);
+        *world.resource_mut::<RoomId>() = self.room_id;
+        *world.resource_mut::<Layout>() = self.layout;
+        *world.resource_mut::<resource::Log>() = self.log;
+        world.insert_resource(self.random);
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        Self::decode(&fs::read_to_string(path)?)
+    }
+
+    /// The save as a single line of RON, e.g. for embedding it elsewhere.
+    pub fn encode(&self) -> Result<String, SaveError> {
+        Ok(ron::to_string(self)?)
+    }
+
+    pub fn decode(s: &str) -> Result<Self, SaveError> {
+        Ok(ron::from_str(s)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let s = ron::ser::to_string_pretty(self, Default::default())?;
+        fs::write(path, s)?;
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn insert<T: Component>(world: &mut World, entity: Entity, component: T) {
+    world.entity_mut(entity).insert(component);
+}
+
+fn components(world: &World, entity: Entity) -> Vec<SavedComponent> {
+    let mut components = Vec::new();
+
+    macro_rules! save {
+        ($($component:ident),*) => {
+            $(
+                if let Some(it) = world.get::<$component>(entity) {
+                    components.push(SavedComponent::$component(it.clone()));
+                }
+            )*
+        };
+    }
+
+    save!(
+        Player,
+        Description,
+        Wall,
+        Floor,
+        God,
+        Position,
+        DoorKind,
+        Door,
+        Renderable,
+        AIMemory,
+        AI,
+        Speed,
+        Opaque,
+        Solid,
+        Damage,
+        MeleeWeapon,
+        Projectile,
+        RangedWeapon,
+        ProjectileGun,
+        Beam,
+        EnergyGun,
+        PowerSetting,
+        PersonalBattery,
+        MeleeSlot,
+        GunSlot,
+        Equipped,
+        Vulnerable,
+        Alive,
+        Destructible,
+        RoomSpawner
+    );
+
+    if let Some(sight) = world.get::<Sight>(entity) {
+        components.push(SavedComponent::Sight(sight.kind));
+    }
+
+    if let Some(memory) = world.get::<Memory>(entity) {
+        let mut memories: Vec<_> = memory
+            .0
+            .iter()
+            .map(|(entity, components)| SavedMemory {
+                entity: entity.id(),
+                components: components.clone(),
+            })
+            .collect();
+        memories.sort_by_key(|memory| memory.entity);
+
+        components.push(SavedComponent::Memory(memories));
+    }
+
+    if world.get::<Speed>(entity).is_some() {
+        let energy = world.resource::<resource::Scheduler>().energy(entity);
+        components.push(SavedComponent::Energy(energy));
+    }
+
+    if let Some(item) = world.get::<Item>(entity) {
+        components.push(SavedComponent::Item {
+            owner: item.owner.map(|owner| owner.id()),
+        });
+    }
+
+    if let Some(magazine) = world.get::<Magazine>(entity) {
+        components.push(SavedComponent::Magazine {
+            gun: magazine.gun,
+            projectile: magazine.projectile.clone(),
+            amount: magazine.amount,
+            attached: magazine.attached.map(|gun| gun.id()),
+        });
+    }
+
+    if let Some(gateway) = world.get::<Gateway>(entity) {
+        components.push(SavedComponent::Gateway {
+            twin: gateway.twin.id(),
+            direction: gateway.direction,
+        });
+    }
+
+    if let Some(lock) = world.get::<Lock>(entity) {
+        let mut locked: Vec<_> = lock.locked.iter().map(|entity| entity.id()).collect();
+        locked.sort();
+
+        components.push(SavedComponent::Lock {
+            active: lock.active,
+            locked,
+        });
+    }
+
+    if let Some(switch) = world.get::<Switch>(entity) {
+        components.push(SavedComponent::Switch {
+            targets: switch.targets.iter().map(|entity| entity.id()).collect(),
+        });
+    }
+
+    if let Some(parent) = world.get::<Parent>(entity) {
+        components.push(SavedComponent::Parent(parent.get().id()));
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{Action, Game, GatewaySpawnAction, Settings, ViewAction};
+
+    use super::*;
+
+    /// `save` with its entities numbered in order, so that saves of the same
+    /// world compare equal whatever ids their entities had.
+    fn renumber(mut save: SaveGame) -> SaveGame {
+        save.entities.retain(|saved| !saved.components.is_empty());
+
+        let mut ids: HashMap<u32, u32> = save
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(i, saved)| (saved.id, i as u32))
+            .collect();
+        let mut id = |old: u32| -> u32 {
+            let next = ids.len() as u32;
+            *ids.entry(old).or_insert(next)
+        };
+
+        for saved in save.entities.iter_mut() {
+            saved.id = id(saved.id);
+
+            for component in saved.components.iter_mut() {
+                match component {
+                    SavedComponent::Memory(memories) => {
+                        for memory in memories.iter_mut() {
+                            memory.entity = id(memory.entity);
+                        }
+                        memories.sort_by_key(|memory| memory.entity);
+                    }
+                    SavedComponent::Item { owner } => *owner = owner.map(&mut id),
+                    SavedComponent::Magazine { attached, .. } => *attached = attached.map(&mut id),
+                    SavedComponent::Gateway { twin, .. } => *twin = id(*twin),
+                    SavedComponent::Lock { locked, .. } => {
+                        for entity in locked.iter_mut() {
+                            *entity = id(*entity);
+                        }
+                        locked.sort();
+                    }
+                    SavedComponent::Switch { targets } => {
+                        for entity in targets.iter_mut() {
+                            *entity = id(*entity);
+                        }
+                    }
+                    SavedComponent::Parent(parent) => *parent = id(*parent),
+                    _ => {}
+                }
+            }
+        }
+
+        save
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut game = Game::new(Settings {
+            seed: Some(1),
+            level: Some("level01".to_string()),
+        })
+        .unwrap();
+
+        // Level 01 has no gateways of its own, so link two of its doors
+        let doors: Vec<_> = game
+            .world
+            .query_filtered::<&Position, (With<Door>, Without<Parent>)>()
+            .iter(&game.world)
+            .copied()
+            .collect();
+        game.input(Action::SpawnGateway(GatewaySpawnAction {
+            lhs: doors[0],
+            rhs: doors[1],
+            direction: Direction::East,
+        }));
+        // Loading views everything again, which now sees through the gateway
+        game.input(Action::View(ViewAction::All));
+
+        // Rooms generated after loading come from the saved catalogue
+        game.world.insert_resource(Rooms::from_iter([(
+            "corridor".to_string(),
+            "#####\n|···|\n#####".parse().unwrap(),
+        )]));
+
+        let save: SaveGame = ron::from_str(&ron::to_string(&game.save()).unwrap()).unwrap();
+        let expected = ron::to_string(&renumber(game.save())).unwrap();
+
+        let mut game = Game::load(save);
+        assert_eq!(ron::to_string(&renumber(game.save())).unwrap(), expected);
+
+        let rooms = game.world.resource::<Rooms>();
+        assert!(rooms.get("corridor").is_some());
+        assert!(rooms.get("level01").is_none());
+
+        let world = &mut game.world;
+        let mut gateways = world.query::<(Entity, &Gateway)>();
+        let gateways: Vec<_> = gateways.iter(world).map(|(e, g)| (e, g.twin)).collect();
+        assert_eq!(gateways.len(), 2);
+        for (entity, twin) in gateways {
+            assert_eq!(world.get::<Gateway>(twin).unwrap().twin, entity);
+        }
+
+        let mut items = world.query::<&Item>();
+        let owners: Vec<_> = items.iter(world).filter_map(|item| item.owner).collect();
+        assert!(!owners.is_empty());
+        for owner in owners {
+            assert!(world.get::<Alive>(owner).is_some());
+        }
+
+        let mut magazines = world.query::<&Magazine>();
+        let attached: Vec<_> = magazines.iter(world).filter_map(|it| it.attached).collect();
+        assert_eq!(attached.len(), 1);
+        assert!(world.get::<ProjectileGun>(attached[0]).is_some());
+
+        let mut switches = world.query::<&Switch>();
+        let targets: Vec<_> = switches
+            .iter(world)
+            .flat_map(|switch| switch.targets.clone())
+            .collect();
+        assert!(!targets.is_empty());
+        let locks: Vec<_> = targets
+            .iter()
+            .filter_map(|target| world.get::<Lock>(*target))
+            .collect();
+        assert_eq!(locks.len(), 1);
+        for target in targets.iter() {
+            assert!(world.get::<Door>(*target).is_some() || world.get::<Lock>(*target).is_some());
+        }
+        for door in locks[0].locked.iter() {
+            assert!(targets.contains(door));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Journals of the actions a game was played with, for reproducing it.
+//!
+//! A journal starts with a header naming the seed and level, or holding the
+//! whole saved game it started from, followed by every action sent to the game
+//! and the events it emitted in turn. Actions are written before they are processed, so the
+//! journal of a game that panicked ends with the action responsible.
+
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use api::journal_record::Record;
+use api::{ActionRequest, Event, EventsResponse, JournalHeader, JournalRecord};
+use prost::Message;
+use thiserror::Error;
+
+use crate::game::{self, Game, InvalidAction, SaveGame, Settings};
+
+pub struct Journal {
+    file: File,
+}
+
+impl Journal {
+    pub fn create(directory: &Path, session: u32, header: JournalHeader) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = directory.join(format!("{}-{}.journal", timestamp, session));
+
+        fs::create_dir_all(directory)?;
+        let mut journal = Self {
+            file: File::create(&path)?,
+        };
+        journal.write(Record::Header(header))?;
+
+        log::info!("Recording journal to {}", path.display());
+
+        Ok(journal)
+    }
+
+    pub fn action(&mut self, action: &ActionRequest) -> io::Result<()> {
+        self.write(Record::Action(action.clone()))
+    }
+
+    pub fn events(&mut self, events: &[Event]) -> io::Result<()> {
+        self.write(Record::Events(EventsResponse {
+            events: events.to_vec(),
+        }))
+    }
+
+    fn write(&mut self, record: Record) -> io::Result<()> {
+        let record = JournalRecord {
+            record: Some(record),
+        };
+
+        self.file
+            .write_all(&record.encode_length_delimited_to_vec())?;
+        self.file.flush()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Could not read journal: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed journal: {0}")]
+    Decode(#[from] prost::DecodeError),
+    #[error("Journal must start with exactly one header")]
+    Header,
+    #[error(transparent)]
+    Level(#[from] game::UnknownLevel),
+    #[error(transparent)]
+    Save(#[from] game::SaveError),
+    #[error("Action #{0} is invalid: {1}")]
+    Action(usize, InvalidAction),
+    #[error("Action #{action} diverged from the journal at event #{event}")]
+    Diverged { action: usize, event: usize },
+}
+
+/// Feeds the journal at `path` into a fresh game, checking that it emits the
+/// recorded events. Returns the number of actions replayed.
+pub fn replay(path: &Path) -> Result<usize, ReplayError> {
+    let data = fs::read(path)?;
+    let mut buf = data.as_slice();
+    let mut records = Vec::new();
+
+    while !buf.is_empty() {
+        if let Some(record) = JournalRecord::decode_length_delimited(&mut buf)?.record {
+            records.push(record);
+        }
+    }
+
+    let mut records = records.into_iter();
+
+    let mut game = match records.next() {
+        Some(Record::Header(header)) if header.save.is_empty() => Game::new(Settings {
+            seed: Some(header.seed),
+            level: Some(header.level).filter(|level| !level.is_empty()),
+        })?,
+        Some(Record::Header(header)) => Game::load(SaveGame::decode(&header.save)?),
+        _ => return Err(ReplayError::Header),
+    };
+
+    let mut actions = 0;
+    let mut pending = None;
+
+    for record in records {
+        match record {
+            Record::Action(request) => {
+                actions += 1;
+                log::debug!("Replaying action #{} {:?}", actions, request);
+
+                let action = game::Action::try_from(&request)
+                    .map_err(|e| ReplayError::Action(actions, e))?;
+                pending = Some(game.input(action));
+            }
+            Record::Events(EventsResponse { events: expected }) => {
+                let actual = pending.take().unwrap_or_default();
+
+                if actual != expected {
+                    let event = actual
+                        .iter()
+                        .zip(expected.iter())
+                        .position(|(actual, expected)| actual != expected)
+                        .unwrap_or_else(|| actual.len().min(expected.len()));
+
+                    log::error!("Expected {:?}", expected.get(event));
+                    log::error!("Got {:?}", actual.get(event));
+
+                    return Err(ReplayError::Diverged {
+                        action: actions,
+                        event,
+                    });
+                }
+            }
+            Record::Header(_) => return Err(ReplayError::Header),
+        }
+    }
+
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, process};
+
+    use api::action_request::{Action as Request, DwimAction};
+
+    use super::*;
+
+    #[test]
+    fn test_replay_save() {
+        let directory = env::temp_dir().join(format!("reshock-journal-{}", process::id()));
+
+        let mut game = Game::new(Settings {
+            seed: Some(1),
+            level: Some("level01".to_string()),
+        })
+        .unwrap();
+        let save = game.save().encode().unwrap();
+
+        // Recorded from the loaded game, like the server does, with no save
+        // file around to replay from
+        let mut game = Game::load(SaveGame::decode(&save).unwrap());
+        let header = JournalHeader {
+            seed: game.seed(),
+            save,
+            level: String::new(),
+        };
+        let mut journal = Journal::create(&directory, 0, header).unwrap();
+
+        for dwim in [DwimAction::Right, DwimAction::Left] {
+            let request = ActionRequest {
+                action: Some(Request::Dwim(dwim as i32)),
+            };
+            journal.action(&request).unwrap();
+            let events = game.input(game::Action::try_from(&request).unwrap());
+            journal.events(&events).unwrap();
+        }
+
+        // The only file in the directory is the journal
+        let entry = fs::read_dir(&directory).unwrap().next().unwrap();
+        let replayed = replay(&entry.unwrap().path());
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(replayed.unwrap(), 2);
+    }
+}
@@ -0,0 +1,5 @@
+#![feature(downcast_unchecked, is_some_with)]
+
+pub mod config;
+pub mod game;
+pub mod journal;
@@ -1,86 +1,354 @@
-#![feature(downcast_unchecked, is_some_with)]
-
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 use api::reshock_server::*;
 use api::*;
 use env_logger::Env;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 
-use crate::config::Config;
-use crate::game::Game;
+use reshock_server::config::Config;
+use reshock_server::game::{self, Game, SaveGame, Settings};
+use reshock_server::journal::{self, Journal};
+
+use crate::session::{GameSession, DEFAULT_SESSION, SESSION_METADATA_KEY};
 
-mod config;
-mod game;
+mod session;
 
 struct ReshockService {
-    game: Arc<Mutex<Game>>,
+    sessions: RwLock<HashMap<u32, Arc<GameSession>>>,
+    next_session: AtomicU32,
+    save_directory: PathBuf,
+    journal_directory: PathBuf,
+    settings: Settings,
 }
 
 impl ReshockService {
-    fn new(game: Game) -> Self {
-        Self {
-            game: Arc::new(Mutex::new(game)),
+    fn new(config: &Config) -> Result<Self, game::UnknownLevel> {
+        let mut service = Self {
+            sessions: Default::default(),
+            next_session: AtomicU32::new(DEFAULT_SESSION + 1),
+            save_directory: config.save_directory.clone().into(),
+            journal_directory: config.journal_directory.clone().into(),
+            settings: Settings {
+                seed: config.seed,
+                level: config.level.clone(),
+            },
+        };
+
+        let session = service.new_session(DEFAULT_SESSION)?;
+        service.sessions.get_mut().insert(DEFAULT_SESSION, session);
+
+        Ok(service)
+    }
+
+    fn new_session(&self, id: u32) -> Result<Arc<GameSession>, game::UnknownLevel> {
+        let game = Game::new(self.settings.clone())?;
+        let journal = self.journal(id, &game, &self.settings, None);
+
+        Ok(Arc::new(GameSession::new(id, game, journal)))
+    }
+
+    /// Starts a journal for a freshly started or loaded `game`. Failing to
+    /// do so is not fatal, the game just goes unrecorded.
+    fn journal(
+        &self,
+        session: u32,
+        game: &Game,
+        settings: &Settings,
+        save: Option<String>,
+    ) -> Option<Journal> {
+        let header = JournalHeader {
+            seed: game.seed(),
+            save: save.unwrap_or_default(),
+            level: settings.level.clone().unwrap_or_default(),
+        };
+
+        match Journal::create(&self.journal_directory, session, header) {
+            Ok(it) => Some(it),
+            Err(e) => {
+                log::error!("Could not create journal for session {}: {}", session, e);
+                None
+            }
+        }
+    }
+
+    fn save_path(&self, name: &str) -> Result<PathBuf, Status> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if !valid {
+            return Err(Status::invalid_argument(format!(
+                "Invalid save name {:?}",
+                name
+            )));
         }
+
+        Ok(self.save_directory.join(format!("{}.ron", name)))
+    }
+
+    async fn session<T>(&self, request: &Request<T>) -> Result<Arc<GameSession>, Status> {
+        let id = match request.metadata().get(SESSION_METADATA_KEY) {
+            Some(value) => value
+                .to_str()
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| Status::invalid_argument("Malformed session id"))?,
+            None => DEFAULT_SESSION,
+        };
+
+        self.sessions
+            .read()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("No session with id {}", id)))
+    }
+}
+
+/// Fails a query of the player's view, with a dead player being no fault of the
+/// server.
+fn view_status(e: anyhow::Error) -> Status {
+    if e.is::<game::PlayerDead>() {
+        Status::failed_precondition(e.to_string())
+    } else {
+        Status::internal(e.to_string())
     }
 }
 
 #[tonic::async_trait]
 impl Reshock for ReshockService {
+    type SubscribeEventsStream =
+        Pin<Box<dyn Stream<Item = Result<SequencedEvent, Status>> + Send + 'static>>;
+
     async fn dump_state(
         &self,
         request: Request<Empty>,
     ) -> Result<Response<StateDumpResponse>, Status> {
         log::debug!("Reshock::dump_state {:?}", request.get_ref());
-        let mut game = self.game.lock().await;
+        let session = self.session(&request).await?;
+        let mut game = session.game.lock().await;
         let response = game.state().map_err(|_| Status::internal("Bla"))?;
 
         Ok(Response::new(response))
     }
 
+    async fn dump_map(&self, request: Request<Empty>) -> Result<Response<MapResponse>, Status> {
+        log::debug!("Reshock::dump_map {:?}", request.get_ref());
+        let session = self.session(&request).await?;
+        let mut game = session.game.lock().await;
+        let response = game.map().map_err(view_status)?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn examine(
+        &self,
+        request: Request<ExamineRequest>,
+    ) -> Result<Response<ExamineResponse>, Status> {
+        log::debug!("Reshock::examine {:?}", request.get_ref());
+        let session = self.session(&request).await?;
+        let position = request
+            .get_ref()
+            .position
+            .as_ref()
+            .map(|it| glam::ivec2(it.x, it.y))
+            .ok_or_else(|| Status::invalid_argument("Missing position"))?;
+
+        let mut game = session.game.lock().await;
+        let response = game.examine(position).map_err(view_status)?;
+
+        Ok(Response::new(response))
+    }
+
     async fn process_action(
         &self,
         request: Request<ActionRequest>,
     ) -> Result<Response<EventsResponse>, Status> {
-        use action_request::{Action, DwimAction};
-
         log::debug!("Reshock::process_action {:?}", request.get_ref());
-        let mut game = self.game.lock().await;
-
-        let action = match request.into_inner().action {
-            Some(Action::Dwim(direction)) => match DwimAction::from_i32(direction) {
-                Some(DwimAction::UpLeft) => game::Action::Dwim(game::DwimAction::UpLeft),
-                Some(DwimAction::Up) => game::Action::Dwim(game::DwimAction::Up),
-                Some(DwimAction::UpRight) => game::Action::Dwim(game::DwimAction::UpRight),
-                Some(DwimAction::Right) => game::Action::Dwim(game::DwimAction::Right),
-                Some(DwimAction::DownRight) => game::Action::Dwim(game::DwimAction::DownRight),
-                Some(DwimAction::Down) => game::Action::Dwim(game::DwimAction::Down),
-                Some(DwimAction::DownLeft) => game::Action::Dwim(game::DwimAction::DownLeft),
-                Some(DwimAction::Left) => game::Action::Dwim(game::DwimAction::Left),
-                Some(DwimAction::Close) => game::Action::Dwim(game::DwimAction::Close),
-                Some(DwimAction::Shoot) => game::Action::Dwim(game::DwimAction::Shoot),
-                None => return Err(Status::invalid_argument("Dwim index out of bounds")),
-            },
-            Some(Action::God(_)) => game::Action::GodMode(game::GodModeAction::Intent),
-            None => return Err(Status::invalid_argument("Action not set")),
-        };
+        let session = self.session(&request).await?;
+        let request = request.into_inner();
 
+        let action = game::Action::try_from(&request)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let mut game = session.game.lock().await;
+
+        session.record(|journal| journal.action(&request)).await;
         let events = game.input(action);
+        session.record(|journal| journal.events(&events)).await;
+
+        session.publish(&events);
 
-        Ok(Response::new(EventsResponse { events }))
+        Ok(Response::new(EventsResponse {
+            events: game::compact(events),
+        }))
     }
 
     async fn restart(
         &self,
-        request: Request<Empty>,
+        request: Request<RestartRequest>,
     ) -> Result<Response<StateDumpResponse>, Status> {
         log::debug!("Reshock::restart {:?}", request.get_ref());
-        let mut game = self.game.lock().await;
-        *game = Game::default();
+        let session = self.session(&request).await?;
+        let settings = Settings {
+            seed: request.get_ref().seed.or(self.settings.seed),
+            ..self.settings.clone()
+        };
+
+        let mut game = session.game.lock().await;
+        *game = Game::new(settings.clone()).map_err(|e| Status::internal(e.to_string()))?;
+        session
+            .set_journal(self.journal(session.id, &game, &settings, None))
+            .await;
         let response = game.state().map_err(|_| Status::internal("Bla"))?;
 
+        session.publish(&[Event {
+            event: Some(event::Event::State(StateUpdateEvent {
+                player: response.player,
+                state: response.state.clone(),
+                diff: None,
+            })),
+        }]);
+
+        Ok(Response::new(response))
+    }
+
+    async fn subscribe_events(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        log::debug!("Reshock::subscribe_events {:?}", request.get_ref());
+        let session = self.session(&request).await?;
+
+        let stream = BroadcastStream::new(session.subscribe()).filter_map(
+            |result| -> Option<Result<SequencedEvent, Status>> {
+                match result {
+                    Ok(event) => Some(Ok(event)),
+                    Err(BroadcastStreamRecvError::Lagged(n)) => {
+                        log::warn!("Subscriber lagged behind, skipping {} events", n);
+                        None
+                    }
+                }
+            },
+        );
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn create_session(&self, request: Request<Empty>) -> Result<Response<Session>, Status> {
+        log::debug!("Reshock::create_session {:?}", request.get_ref());
+        let id = self.next_session.fetch_add(1, Ordering::SeqCst);
+        let session = self
+            .new_session(id)
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        self.sessions.write().await.insert(id, session);
+        log::info!("Created session {}", id);
+
+        Ok(Response::new(Session { id }))
+    }
+
+    async fn list_sessions(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<SessionsResponse>, Status> {
+        log::debug!("Reshock::list_sessions {:?}", request.get_ref());
+        let mut ids: Vec<_> = self.sessions.read().await.keys().copied().collect();
+        ids.sort();
+
+        Ok(Response::new(SessionsResponse {
+            sessions: ids.into_iter().map(|id| Session { id }).collect(),
+        }))
+    }
+
+    async fn close_session(&self, request: Request<Session>) -> Result<Response<Empty>, Status> {
+        log::debug!("Reshock::close_session {:?}", request.get_ref());
+        let Session { id } = request.into_inner();
+
+        if id == DEFAULT_SESSION {
+            return Err(Status::failed_precondition(
+                "The default session can't be closed",
+            ));
+        }
+
+        match self.sessions.write().await.remove(&id) {
+            Some(_) => {
+                log::info!("Closed session {}", id);
+                Ok(Response::new(Empty {}))
+            }
+            None => Err(Status::not_found(format!("No session with id {}", id))),
+        }
+    }
+
+    async fn save_game(
+        &self,
+        request: Request<SaveGameRequest>,
+    ) -> Result<Response<Empty>, Status> {
+        log::debug!("Reshock::save_game {:?}", request.get_ref());
+        let session = self.session(&request).await?;
+        let path = self.save_path(&request.get_ref().name)?;
+        let mut game = session.game.lock().await;
+
+        game.save().write(&path).map_err(|e| {
+            log::error!("Could not save game to {}: {}", path.display(), e);
+            Status::internal(e.to_string())
+        })?;
+        log::info!("Saved game to {}", path.display());
+
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn load_game(
+        &self,
+        request: Request<LoadGameRequest>,
+    ) -> Result<Response<StateDumpResponse>, Status> {
+        log::debug!("Reshock::load_game {:?}", request.get_ref());
+        let session = self.session(&request).await?;
+        let path = self.save_path(&request.get_ref().name)?;
+
+        if !path.exists() {
+            return Err(Status::not_found(format!(
+                "No saved game {:?}",
+                request.get_ref().name
+            )));
+        }
+
+        let save = SaveGame::read(&path).map_err(|e| {
+            log::error!("Could not load game from {}: {}", path.display(), e);
+            Status::internal(e.to_string())
+        })?;
+
+        // The journal holds the save itself, replaying even once the file is
+        // gone or overwritten
+        let encoded = save.encode().map_err(|e| Status::internal(e.to_string()))?;
+
+        let mut game = session.game.lock().await;
+        *game = Game::load(save);
+        session
+            .set_journal(self.journal(session.id, &game, &self.settings, Some(encoded)))
+            .await;
+        let response = game.state().map_err(|e| Status::internal(e.to_string()))?;
+        log::info!("Loaded game from {}", path.display());
+
+        session.publish(&[Event {
+            event: Some(event::Event::State(StateUpdateEvent {
+                player: response.player,
+                state: response.state.clone(),
+                diff: None,
+            })),
+        }]);
+
         Ok(Response::new(response))
     }
 }
@@ -90,18 +358,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init_from_env(Env::default().default_filter_or("info"));
     let config = Config::new()?;
 
+    if let Some(path) = &config.replay {
+        let actions = journal::replay(Path::new(path))?;
+        log::info!(
+            "Replayed {} actions from {}, all events match",
+            actions,
+            path
+        );
+
+        return Ok(());
+    }
+
     let address = config.listen_address.parse()?;
 
     let reflector = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(api::FILE_DESCRIPTOR_SET)
         .build()?;
 
-    let game = Game::default();
+    let service = ReshockService::new(&config)?;
 
     log::info!("Running on {}", address);
 
     Server::builder()
-        .add_service(ReshockServer::new(ReshockService::new(game)))
+        .add_service(ReshockServer::new(service))
         .add_service(reflector)
         .serve(address)
         .await?;
@@ -0,0 +1,74 @@
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use api::{Event, SequencedEvent};
+use tokio::sync::{broadcast, Mutex};
+
+use reshock_server::game::Game;
+use reshock_server::journal::Journal;
+
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Metadata key clients use to address a session; requests without it go to
+/// the default session.
+pub const SESSION_METADATA_KEY: &str = "reshock-session";
+pub const DEFAULT_SESSION: u32 = 0;
+
+pub struct GameSession {
+    pub id: u32,
+    pub game: Mutex<Game>,
+    journal: Mutex<Option<Journal>>,
+    events: broadcast::Sender<SequencedEvent>,
+    sequence: AtomicU64,
+}
+
+impl GameSession {
+    pub fn new(id: u32, game: Game, journal: Option<Journal>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        Self {
+            id,
+            game: Mutex::new(game),
+            journal: Mutex::new(journal),
+            events,
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Hands `events` to all subscribers, in order. Callers must hold the game
+    /// lock so sequence numbers follow the order in which events happened.
+    pub fn publish(&self, events: &[Event]) {
+        for event in events {
+            let sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
+
+            // Sending only fails if nobody is subscribed, which is fine
+            let _ = self.events.send(SequencedEvent {
+                sequence,
+                event: Some(event.clone()),
+            });
+        }
+    }
+
+    /// Replaces the journal, to be called whenever the game is replaced.
+    pub async fn set_journal(&self, journal: Option<Journal>) {
+        *self.journal.lock().await = journal;
+    }
+
+    /// Writes to the journal, if any. A journal that fails to write is closed
+    /// rather than left with gaps.
+    pub async fn record<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Journal) -> io::Result<()>,
+    {
+        let mut journal = self.journal.lock().await;
+
+        if let Some(Err(e)) = journal.as_mut().map(f) {
+            log::error!("Could not write journal of session {}: {}", self.id, e);
+            *journal = None;
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SequencedEvent> {
+        self.events.subscribe()
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::math::vec2;
 use bevy::prelude::*;
 use bevy_prototype_lyon::entity::ShapeBundle;
@@ -72,6 +74,33 @@ impl Projectile {
     }
 }
 
+#[derive(Bundle)]
+pub struct Beam {
+    pub effect: component::Effect,
+
+    #[bundle]
+    pub shape: ShapeBundle,
+}
+
+impl Beam {
+    pub fn new(start: Vec2, end: Vec2) -> Self {
+        let shape = shapes::Line(start, end);
+        let shape = GeometryBuilder::build_as(
+            &shape,
+            DrawMode::Stroke(StrokeMode::new(Color::CYAN, 3.0)),
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        );
+
+        Self {
+            effect: component::Effect {
+                lifetime: Timer::new(Duration::from_secs_f32(0.2), false),
+                remove: true,
+            },
+            shape,
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct Marker {
     pub position: component::Position,
@@ -10,6 +10,15 @@ pub struct ReshockEntity(pub u32);
 #[derive(Component)]
 pub struct Player;
 
+impl Player {
+    /// Where the player is in the state, relative to the player's room. Hits,
+    /// shots and examined positions are relative to the player instead, so
+    /// they're offset by this.
+    pub fn origin(player: &Query<&Position, With<Player>>) -> IVec2 {
+        player.get_single().map_or(IVec2::ZERO, |it| it.0)
+    }
+}
+
 #[derive(Component)]
 pub struct Focus;
 
@@ -19,6 +28,10 @@ pub struct Wall;
 #[derive(Component)]
 pub struct Memory;
 
+/// Hostile actors in sight, which can be targeted.
+#[derive(Component)]
+pub struct Enemy;
+
 #[derive(Clone, Debug)]
 pub enum Ordering {
     Floor,
@@ -107,4 +120,8 @@ pub struct Velocity(pub Vec2);
 pub enum Marker {
     Selection,
     Active(IVec2),
+    /// Targeting the entity with the given Reshock id.
+    Target(u32),
+    /// Looking at what's at the given position.
+    Look(IVec2),
 }
@@ -1,11 +1,14 @@
 use config::{ConfigError, Environment, File, FileFormat};
 use serde::Deserialize;
 
+use crate::keymap::KeymapConfig;
+
 const DEFAULTS: &str = include_str!("defaults.toml");
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Config {
     pub reshock_url: String,
+    pub keymap: KeymapConfig,
 }
 
 impl Config {
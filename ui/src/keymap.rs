@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use api::action_request::DwimAction;
+use bevy::prelude::*;
+use itertools::Itertools;
+use serde::Deserialize;
+
+/// Keys setting the energy weapon power level, from 1 to 10
+pub const POWER_KEYS: [KeyCode; 10] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Key0,
+];
+
+/// What a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Dwim(DwimAction),
+    Target,
+    Reload,
+    Eject,
+    PickUp,
+    God,
+    Look,
+    Map,
+    /// Pressed together with shift, so it may share its key with another
+    /// binding.
+    Restart,
+}
+
+/// Bindings by their name in the config.
+const BINDINGS: [(&str, Binding); 18] = [
+    ("up-left", Binding::Dwim(DwimAction::UpLeft)),
+    ("up", Binding::Dwim(DwimAction::Up)),
+    ("up-right", Binding::Dwim(DwimAction::UpRight)),
+    ("right", Binding::Dwim(DwimAction::Right)),
+    ("down-right", Binding::Dwim(DwimAction::DownRight)),
+    ("down", Binding::Dwim(DwimAction::Down)),
+    ("down-left", Binding::Dwim(DwimAction::DownLeft)),
+    ("left", Binding::Dwim(DwimAction::Left)),
+    ("close", Binding::Dwim(DwimAction::Close)),
+    ("shoot", Binding::Dwim(DwimAction::Shoot)),
+    ("target", Binding::Target),
+    ("reload", Binding::Reload),
+    ("eject", Binding::Eject),
+    ("pick-up", Binding::PickUp),
+    ("god", Binding::God),
+    ("look", Binding::Look),
+    ("map", Binding::Map),
+    ("restart", Binding::Restart),
+];
+
+impl Binding {
+    fn parse(name: &str) -> Option<Self> {
+        BINDINGS
+            .iter()
+            .find_map(|(it, binding)| (*it == name).then_some(*binding))
+    }
+
+    fn name(&self) -> &'static str {
+        BINDINGS
+            .iter()
+            .find_map(|(name, it)| (it == self).then_some(*name))
+            .unwrap()
+    }
+
+    /// The direction a movement binding points at.
+    pub fn direction(&self) -> Option<IVec2> {
+        let (x, y) = match self {
+            Binding::Dwim(DwimAction::UpLeft) => (-1, 1),
+            Binding::Dwim(DwimAction::Up) => (0, 1),
+            Binding::Dwim(DwimAction::UpRight) => (1, 1),
+            Binding::Dwim(DwimAction::Right) => (1, 0),
+            Binding::Dwim(DwimAction::DownRight) => (1, -1),
+            Binding::Dwim(DwimAction::Down) => (0, -1),
+            Binding::Dwim(DwimAction::DownLeft) => (-1, -1),
+            Binding::Dwim(DwimAction::Left) => (-1, 0),
+            _ => return None,
+        };
+
+        Some(IVec2::new(x, y))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// Movement on the left hand of a Dvorak layout
+    Dvorak,
+    /// Movement on `hjklyubn`
+    Vi,
+    /// Movement on the numeric keypad
+    Numpad,
+}
+
+impl Preset {
+    fn keys(&self) -> HashMap<Binding, KeyCode> {
+        let movement = match self {
+            Preset::Dvorak => [
+                KeyCode::Apostrophe,
+                KeyCode::Comma,
+                KeyCode::Period,
+                KeyCode::E,
+                KeyCode::J,
+                KeyCode::Q,
+                KeyCode::Semicolon,
+                KeyCode::A,
+            ],
+            Preset::Vi => [
+                KeyCode::Y,
+                KeyCode::K,
+                KeyCode::U,
+                KeyCode::L,
+                KeyCode::N,
+                KeyCode::J,
+                KeyCode::B,
+                KeyCode::H,
+            ],
+            Preset::Numpad => [
+                KeyCode::Numpad7,
+                KeyCode::Numpad8,
+                KeyCode::Numpad9,
+                KeyCode::Numpad6,
+                KeyCode::Numpad3,
+                KeyCode::Numpad2,
+                KeyCode::Numpad1,
+                KeyCode::Numpad4,
+            ],
+        };
+
+        // `l` moves right with vi keys
+        let look = match self {
+            Preset::Vi => KeyCode::Semicolon,
+            _ => KeyCode::L,
+        };
+
+        BINDINGS
+            .iter()
+            .map(|(_, binding)| *binding)
+            .zip(movement)
+            .chain([
+                (Binding::Dwim(DwimAction::Close), KeyCode::C),
+                (Binding::Dwim(DwimAction::Shoot), KeyCode::F),
+                (Binding::Target, KeyCode::T),
+                (Binding::Reload, KeyCode::R),
+                (Binding::Eject, KeyCode::X),
+                (Binding::PickUp, KeyCode::P),
+                (Binding::God, KeyCode::G),
+                (Binding::Look, look),
+                (Binding::Map, KeyCode::M),
+                (Binding::Restart, KeyCode::R),
+            ])
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct KeymapConfig {
+    pub preset: Preset,
+    /// Key names by binding name, replacing the keys of the preset
+    #[serde(default)]
+    pub bindings: HashMap<String, String>,
+}
+
+/// The keys bound to what the player can do.
+pub struct Keymap {
+    bindings: HashMap<KeyCode, Binding>,
+    restart: KeyCode,
+}
+
+impl Keymap {
+    /// Builds the keymap from `config`, or lists everything wrong with it.
+    pub fn new(config: &KeymapConfig) -> Result<Self, Vec<String>> {
+        let mut keys = config.preset.keys();
+        let mut problems = Vec::new();
+
+        for (name, key) in config.bindings.iter().sorted() {
+            let binding = match Binding::parse(name) {
+                Some(it) => it,
+                None => {
+                    problems.push(format!("Unknown action {:?}", name));
+                    continue;
+                }
+            };
+
+            match key_code(key) {
+                Some(key) => {
+                    keys.insert(binding, key);
+                }
+                None => problems.push(format!("Unknown key {:?} for {}", key, name)),
+            }
+        }
+
+        let mut bindings = HashMap::new();
+
+        for (binding, key) in keys
+            .iter()
+            .filter(|(binding, _)| **binding != Binding::Restart)
+            .sorted_by_key(|(binding, _)| binding.name())
+        {
+            if POWER_KEYS.contains(key) {
+                problems.push(format!(
+                    "{:?} for {} already sets the power level",
+                    key,
+                    binding.name()
+                ));
+            } else if let Some(other) = bindings.insert(*key, *binding) {
+                problems.push(format!(
+                    "{:?} is bound to both {} and {}",
+                    key,
+                    other.name(),
+                    binding.name()
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(Self {
+                bindings,
+                restart: keys[&Binding::Restart],
+            })
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// The binding of a key just pressed, if any.
+    pub fn just_pressed(&self, keys: &Input<KeyCode>) -> Option<Binding> {
+        keys.get_just_pressed()
+            .find_map(|key| self.bindings.get(key).copied())
+    }
+
+    pub fn restart(&self) -> KeyCode {
+        self.restart
+    }
+}
+
+macro_rules! key_codes {
+    ($($key:ident),* $(,)?) => {
+        /// The `KeyCode` of the given name.
+        fn key_code(name: &str) -> Option<KeyCode> {
+            match name {
+                $(stringify!($key) => Some(KeyCode::$key),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_codes! {
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K, L,
+    M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
+    F11, F12, Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back, Return,
+    Space, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8,
+    Numpad9, NumpadAdd, NumpadDecimal, NumpadDivide, NumpadEnter, NumpadMultiply,
+    NumpadSubtract, Apostrophe, Backslash, Comma, Equals, Grave, LBracket, Minus, Period,
+    RBracket, Semicolon, Slash, Tab,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(preset: Preset, bindings: &[(&str, &str)]) -> KeymapConfig {
+        KeymapConfig {
+            preset,
+            bindings: bindings
+                .iter()
+                .map(|(binding, key)| (binding.to_string(), key.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in [Preset::Dvorak, Preset::Vi, Preset::Numpad] {
+            let keymap = Keymap::new(&config(preset, &[])).unwrap();
+            assert_eq!(keymap.bindings.len(), BINDINGS.len() - 1);
+        }
+    }
+
+    #[test]
+    fn test_bindings() {
+        let keymap = Keymap::new(&config(Preset::Vi, &[("look", "V")])).unwrap();
+        assert_eq!(keymap.bindings.get(&KeyCode::V), Some(&Binding::Look));
+        assert_eq!(keymap.bindings.get(&KeyCode::Semicolon), None);
+
+        assert_eq!(
+            Keymap::new(&config(
+                Preset::Vi,
+                &[("jump", "V"), ("look", "Fn"), ("map", "H"), ("god", "Key3")]
+            ))
+            .err(),
+            Some(vec![
+                "Unknown action \"jump\"".to_string(),
+                "Unknown key \"Fn\" for look".to_string(),
+                "Key3 for god already sets the power level".to_string(),
+                "H is bound to both left and map".to_string(),
+            ])
+        );
+    }
+}
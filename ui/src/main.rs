@@ -1,6 +1,6 @@
 #![feature(iter_intersperse)]
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use api::reshock_client::ReshockClient;
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
@@ -12,11 +12,13 @@ use component::Renderable;
 use tokio::runtime::Runtime;
 
 use crate::config::Config;
+use crate::keymap::Keymap;
 use crate::plugin::*;
 
 mod bundle;
 mod component;
 mod config;
+mod keymap;
 mod plugin;
 mod resource;
 mod system;
@@ -30,6 +32,8 @@ fn start_background_audio(asset_server: Res<AssetServer>, audio: Res<Audio>) {
 fn main() -> Result<()> {
     let runtime = Runtime::new()?;
     let config = Config::new().context("Could not load configuration")?;
+    let keymap = Keymap::new(&config.keymap)
+        .map_err(|problems| anyhow!("Invalid keymap:\n{}", problems.join("\n")))?;
     let client = runtime
         .block_on(ReshockClient::connect(config.reshock_url))
         .context("Couldn't connect to Reshock server")?;
@@ -50,13 +54,19 @@ fn main() -> Result<()> {
         .add_plugin(MarkerPlugin)
         .add_plugin(EguiPlugin)
         .add_plugin(UiPlugin)
+        .add_plugin(MapPlugin)
+        .add_plugin(LookPlugin)
         .add_plugin(ClientPlugin)
         .add_plugin(ShapePlugin)
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(runtime)
         .insert_resource(client)
+        .insert_resource(keymap)
         .init_resource::<resource::Deltas>()
         .init_resource::<resource::Log>()
+        .init_resource::<resource::Inventory>()
+        .init_resource::<resource::PlayerStatus>()
+        .init_resource::<resource::StateVersion>()
         .add_startup_system(start_background_audio)
         .add_system(system::input)
         .add_system(system::view.label("view"))
@@ -9,6 +9,13 @@ use crate::resource::*;
 
 pub struct RestartEvent;
 
+/// Asks the server for a full snapshot of the state, for when state diffs no
+/// longer apply.
+pub struct DumpStateEvent;
+
+/// An action of the player to send to the server.
+pub struct ActionEvent(pub api::action_request::Action);
+
 #[derive(Default)]
 pub struct GameState(Option<StateDumpResponse>);
 
@@ -17,9 +24,13 @@ pub struct ClientPlugin;
 impl Plugin for ClientPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<RestartEvent>()
+            .add_event::<DumpStateEvent>()
+            .add_event::<ActionEvent>()
             .insert_resource(GameState(None))
             .add_startup_system(setup)
             .add_system(restart)
+            .add_system(dump)
+            .add_system(act)
             .add_system(load);
     }
 }
@@ -52,7 +63,7 @@ pub fn restart(
     }
 
     runtime.block_on(async move {
-        match client.restart(api::Empty {}).await {
+        match client.restart(api::RestartRequest { seed: None }).await {
             Ok(response) => {
                 *state = GameState(Some(response.into_inner()));
             }
@@ -63,19 +74,81 @@ pub fn restart(
     });
 }
 
+pub fn dump(
+    reader: EventReader<DumpStateEvent>,
+    mut client: ResMut<ReshockClient<Channel>>,
+    runtime: Res<Runtime>,
+    mut state: ResMut<GameState>,
+) {
+    if reader.is_empty() {
+        return;
+    }
+
+    runtime.block_on(async move {
+        match client.dump_state(api::Empty {}).await {
+            Ok(response) => {
+                *state = GameState(Some(response.into_inner()));
+            }
+            Err(e) => {
+                log::error!("Could not load Reshock server state: {}", e);
+            }
+        }
+    });
+}
+
+pub fn act(
+    mut reader: EventReader<ActionEvent>,
+    mut client: ResMut<ReshockClient<Channel>>,
+    runtime: Res<Runtime>,
+    mut events: ResMut<ReshockEvents>,
+) {
+    // One action at a time, after the events of the last one have played out
+    if !events.queue.is_empty() {
+        return;
+    }
+
+    let ActionEvent(action) = match reader.iter().next() {
+        Some(it) => it,
+        None => return,
+    };
+
+    runtime.block_on(async move {
+        match client
+            .process_action(api::ActionRequest {
+                action: Some(action.clone()),
+            })
+            .await
+        {
+            Ok(response) => {
+                events.queue = response.into_inner().events.into();
+                log::debug!("Received event queue {}", *events);
+            }
+            Err(e) => {
+                log::warn!("Couldn't process action {}", e);
+            }
+        }
+    });
+}
+
 pub fn load(
     mut log_res: ResMut<Log>,
     mut game_state: ResMut<GameState>,
     mut writer: EventWriter<api::StateUpdateEvent>,
     mut events: ResMut<ReshockEvents>,
 ) {
-    let StateDumpResponse { player, state, log } = match game_state.0.to_owned() {
+    let StateDumpResponse {
+        player, state, log, ..
+    } = match game_state.0.to_owned() {
         Some(it) => it,
         None => return,
     };
 
     events.transitions += 1;
-    writer.send(api::StateUpdateEvent { player, state });
+    writer.send(api::StateUpdateEvent {
+        player,
+        state,
+        diff: None,
+    });
 
     if let Some(api::Log { entries }) = log {
         log_res.0 = entries;
@@ -0,0 +1,146 @@
+use api::examined_entity::Health;
+use api::reshock_client::ReshockClient;
+use bevy::log;
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Align2, RichText},
+    EguiContext,
+};
+use tokio::runtime::Runtime;
+use tonic::transport::Channel;
+
+use crate::component::{Marker, Player, Position};
+
+pub struct LookPlugin;
+
+impl Plugin for LookPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Examination>()
+            .add_system(examine)
+            .add_system(panel);
+    }
+}
+
+/// What the player sees at the position looked at, if looking around.
+#[derive(Default)]
+pub struct Examination(Option<(IVec2, Vec<api::ExaminedEntity>)>);
+
+/// Examines the position looked at whenever it or the state changes.
+fn examine(
+    marker: Query<&Marker>,
+    player: Query<&Position, With<Player>>,
+    mut reader: EventReader<api::StateUpdateEvent>,
+    mut examination: ResMut<Examination>,
+    mut client: ResMut<ReshockClient<Channel>>,
+    runtime: Res<Runtime>,
+) {
+    let updated = reader.iter().last().is_some();
+
+    let pos = match marker.single() {
+        Marker::Look(pos) => *pos,
+        _ => {
+            if examination.0.is_some() {
+                examination.0 = None;
+            }
+            return;
+        }
+    };
+
+    if !updated && matches!(&examination.0, Some((it, _)) if *it == pos) {
+        return;
+    }
+
+    // Examined positions are relative to the player
+    let origin = Player::origin(&player);
+    let position = pos - origin;
+
+    runtime.block_on(async move {
+        match client
+            .examine(api::ExamineRequest {
+                position: Some(api::Position {
+                    x: position.x,
+                    y: position.y,
+                }),
+            })
+            .await
+        {
+            Ok(response) => {
+                examination.0 = Some((pos, response.into_inner().entities));
+            }
+            Err(e) => {
+                log::warn!("Could not examine {}: {}", position, e);
+            }
+        }
+    });
+}
+
+fn panel(mut egui_ctx: ResMut<EguiContext>, examination: Res<Examination>) {
+    let entities = match &examination.0 {
+        Some((_, it)) => it,
+        None => return,
+    };
+
+    let margin = 5.0;
+
+    egui::Window::new("Look")
+        .anchor(Align2::RIGHT_CENTER, [-margin, 0.0])
+        .resizable(false)
+        .title_bar(false)
+        .frame(egui::Frame {
+            stroke: (2.0, egui::Color32::GRAY).into(),
+            fill: egui::Color32::from_rgba_premultiplied(0, 0, 0, 204),
+            inner_margin: 5.0.into(),
+            ..default()
+        })
+        .show(egui_ctx.ctx_mut(), |ui| {
+            if entities.is_empty() {
+                ui.label(RichText::new("Nothing in sight").size(16.0));
+            }
+
+            for entity in entities.iter() {
+                ui.label(RichText::new(describe(entity)).size(16.0));
+            }
+        });
+}
+
+/// The description of `entity`, followed by whatever else can be told about
+/// it.
+fn describe(entity: &api::ExaminedEntity) -> String {
+    let mut details = Vec::new();
+
+    match entity.open {
+        Some(true) => details.push("open".to_string()),
+        Some(false) => details.push("closed".to_string()),
+        None => {}
+    }
+
+    if entity.locked {
+        details.push("locked".to_string());
+    }
+
+    if let Some(health) = entity.health.and_then(Health::from_i32) {
+        details.push(
+            match health {
+                Health::Unhurt => "unhurt",
+                Health::Wounded => "wounded",
+                Health::BadlyWounded => "badly wounded",
+                Health::NearDeath => "near death",
+            }
+            .to_string(),
+        );
+    }
+
+    if let Some(damage) = entity.damage {
+        details.push(format!("{} damage", damage));
+    }
+
+    if let Some(rounds) = entity.rounds {
+        details.push(format!("{} rounds", rounds));
+    }
+
+    if details.is_empty() {
+        entity.description.clone()
+    } else {
+        format!("{} ({})", entity.description, details.join(", "))
+    }
+}
@@ -0,0 +1,174 @@
+use api::renderable_component::Renderable;
+use api::reshock_client::ReshockClient;
+use bevy::log;
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Align2, Color32, Sense},
+    EguiContext,
+};
+use tokio::runtime::Runtime;
+use tonic::transport::Channel;
+
+use crate::keymap::{Binding, Keymap};
+
+/// Size of a map cell in pixels, smallest and largest
+const MIN_ZOOM: f32 = 2.0;
+const MAX_ZOOM: f32 = 24.0;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Overview>()
+            .add_system(load)
+            .add_system(overview);
+    }
+}
+
+/// The overlay showing everything the player remembers of the station.
+pub struct Overview {
+    open: bool,
+    map: Option<api::MapResponse>,
+    /// Of the player from the center of the overlay, in pixels
+    pan: egui::Vec2,
+    /// Size of a map cell, in pixels
+    zoom: f32,
+}
+
+impl Default for Overview {
+    fn default() -> Self {
+        Self {
+            open: false,
+            map: None,
+            pan: egui::Vec2::ZERO,
+            zoom: 6.0,
+        }
+    }
+}
+
+/// Toggles the overlay, loading the map when opening it and keeping it up to
+/// date while open.
+fn load(
+    keys: Res<Input<KeyCode>>,
+    keymap: Res<Keymap>,
+    mut reader: EventReader<api::StateUpdateEvent>,
+    mut overview: ResMut<Overview>,
+    mut client: ResMut<ReshockClient<Channel>>,
+    runtime: Res<Runtime>,
+) {
+    let updated = reader.iter().last().is_some();
+
+    if keymap.just_pressed(&keys) == Some(Binding::Map) {
+        overview.open = !overview.open;
+        overview.pan = egui::Vec2::ZERO;
+    } else if !updated {
+        return;
+    }
+
+    if !overview.open {
+        return;
+    }
+
+    runtime.block_on(async move {
+        match client.dump_map(api::Empty {}).await {
+            Ok(response) => {
+                overview.map = Some(response.into_inner());
+            }
+            Err(e) => {
+                log::warn!("Could not load map: {}", e);
+            }
+        }
+    });
+}
+
+fn overview(
+    mut egui_ctx: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    mut overview: ResMut<Overview>,
+) {
+    let window = match windows.get_primary() {
+        Some(it) => it,
+        None => return,
+    };
+
+    let Overview {
+        open,
+        map,
+        pan,
+        zoom,
+    } = &mut *overview;
+
+    let map = match map {
+        Some(it) if *open => it,
+        _ => return,
+    };
+
+    egui::Window::new("Map")
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .fixed_size((window.width() * 0.75, window.height() * 0.75))
+        .resizable(false)
+        .collapsible(false)
+        .frame(egui::Frame {
+            stroke: (2.0, egui::Color32::GRAY).into(),
+            fill: egui::Color32::from_rgba_premultiplied(0, 0, 0, 230),
+            inner_margin: 5.0.into(),
+            ..default()
+        })
+        .show(egui_ctx.ctx_mut(), |ui| {
+            let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::drag());
+
+            *pan += response.drag_delta();
+            if response.hovered() {
+                let scroll = ui.input().scroll_delta.y;
+                *zoom = (*zoom * (1.0 + scroll / 200.0)).clamp(MIN_ZOOM, MAX_ZOOM);
+            }
+
+            let center = response.rect.center() + *pan;
+            let player = map.player.clone().unwrap_or_default();
+
+            // The game's y axis points up, egui's down
+            let cell = |x: i32, y: i32| {
+                let offset = egui::vec2((x - player.x) as f32, (player.y - y) as f32);
+                egui::Rect::from_center_size(center + offset * *zoom, egui::Vec2::splat(*zoom))
+            };
+
+            let mut cells = Vec::new();
+
+            for room in map.rooms.iter() {
+                // Not placed relative to the player's room
+                let offset = match &room.offset {
+                    Some(it) => it,
+                    None => continue,
+                };
+
+                for (entity, components) in room.entities.iter() {
+                    let renderable = components
+                        .renderable
+                        .as_ref()
+                        .and_then(|it| Renderable::from_i32(it.renderable));
+                    let open = components.door.as_ref().map_or(false, |it| it.open);
+
+                    let (rank, color) = match renderable {
+                        Some(Renderable::Floor) => (0, Color32::from_gray(40)),
+                        Some(Renderable::Wall) => (1, Color32::from_gray(110)),
+                        Some(Renderable::Door) if room.locked.contains(entity) => (2, Color32::RED),
+                        Some(Renderable::Door) if open => (2, Color32::from_gray(60)),
+                        Some(Renderable::Door) => (2, Color32::WHITE),
+                        Some(Renderable::WallSwitch) => (3, Color32::GOLD),
+                        _ => continue,
+                    };
+
+                    for pos in components.positions.iter() {
+                        cells.push((rank, cell(offset.x + pos.x, offset.y + pos.y), color));
+                    }
+                }
+            }
+
+            cells.sort_by_key(|(rank, ..)| *rank);
+            for (_, rect, color) in cells {
+                painter.rect_filled(rect, 0.0, color);
+            }
+
+            painter.rect_filled(cell(player.x, player.y), 0.0, Color32::GREEN);
+        });
+}
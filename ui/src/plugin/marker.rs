@@ -19,6 +19,8 @@ impl Plugin for MarkerPlugin {
         app.add_startup_system(setup)
             .add_system(adapt)
             .add_system(select)
+            .add_system(target)
+            .add_system(look)
             .add_system(color)
             .add_system(activate);
     }
@@ -89,6 +91,75 @@ fn select(
     }
 }
 
+fn target(
+    dimensions: Res<TileDimensions>,
+    entities: Query<(&ReshockEntity, &Position), With<Enemy>>,
+    mut marker: Query<(&mut Transform, &mut Visibility, &mut Marker)>,
+) {
+    let (mut transform, mut visibility, mut marker) = marker.single_mut();
+
+    let id = match *marker {
+        Marker::Target(id) => id,
+        _ => return,
+    };
+
+    let dimensions = match dimensions.0 {
+        Some(Size { width, height }) => vec2(width, height),
+        None => return,
+    };
+
+    // Entities are respawned as their components change, so the target may
+    // be gone by now
+    match entities
+        .iter()
+        .find_map(|(entity, pos)| (entity.0 == id).then_some(pos))
+    {
+        Some(Position(pos)) => {
+            visibility.is_visible = true;
+            transform.translation = (pos.as_vec2() * dimensions - dimensions / 2.0, 2.0).into();
+        }
+        None => {
+            *marker = Marker::Selection;
+        }
+    }
+}
+
+fn look(
+    cursor: Res<Cursor>,
+    dimensions: Res<TileDimensions>,
+    mut last: Local<Option<IVec2>>,
+    mut marker: Query<(&mut Transform, &mut Visibility, &mut Marker)>,
+) {
+    let (mut transform, mut visibility, mut marker) = marker.single_mut();
+
+    // Follows the mouse, but only as long as it moves
+    let moved = cursor.0 != *last;
+    *last = cursor.0;
+
+    if !matches!(*marker, Marker::Look(_)) {
+        return;
+    }
+
+    if moved {
+        if let Some(pos) = cursor.0 {
+            *marker = Marker::Look(pos);
+        }
+    }
+
+    let pos = match *marker {
+        Marker::Look(pos) => pos,
+        _ => return,
+    };
+
+    let dimensions = match dimensions.0 {
+        Some(Size { width, height }) => vec2(width, height),
+        None => return,
+    };
+
+    visibility.is_visible = true;
+    transform.translation = (pos.as_vec2() * dimensions - dimensions / 2.0, 2.0).into();
+}
+
 fn color(mut marker: Query<(&Marker, &mut DrawMode), Changed<Marker>>) {
     let (marker, mut mode) = match marker.get_single_mut() {
         Ok(it) => it,
@@ -118,6 +189,12 @@ fn color(mut marker: Query<(&Marker, &mut DrawMode), Changed<Marker>>) {
                 2.5,
             ));
         }
+        Marker::Target(_) => {
+            *mode = DrawMode::Stroke(StrokeMode::new(Color::ORANGE_RED, 2.5));
+        }
+        Marker::Look(_) => {
+            *mode = DrawMode::Stroke(StrokeMode::new(Color::TURQUOISE, 2.5));
+        }
     }
 }
 
@@ -139,7 +216,7 @@ fn activate(cursor: Res<Cursor>, buttons: Res<Input<MouseButton>>, mut marker: Q
 
     if buttons.just_pressed(MouseButton::Right) {
         match marker.as_ref() {
-            Marker::Active(_) => {
+            Marker::Active(_) | Marker::Target(_) | Marker::Look(_) => {
                 *marker = Marker::Selection;
             }
             _ => {}
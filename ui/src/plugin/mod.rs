@@ -2,15 +2,19 @@ mod camera;
 mod client;
 mod cursor;
 mod door;
+mod look;
+mod map;
 mod marker;
 mod reshock_events;
 mod tile;
 mod ui;
 
 pub use camera::CameraPlugin;
-pub use client::{ClientPlugin, RestartEvent};
+pub use client::{ActionEvent, ClientPlugin, DumpStateEvent, RestartEvent};
 pub use cursor::CursorPlugin;
 pub use door::DoorPlugin;
+pub use look::LookPlugin;
+pub use map::MapPlugin;
 pub use marker::MarkerPlugin;
 pub use reshock_events::ReshockEventsPlugin;
 pub use tile::TilePlugin;
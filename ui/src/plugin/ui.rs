@@ -4,7 +4,8 @@ use bevy_egui::{
     EguiContext,
 };
 
-use crate::resource::Log;
+use crate::plugin::ActionEvent;
+use crate::resource::{Inventory, Log, PlayerStatus, ReshockEvents};
 
 pub struct UiPlugin;
 
@@ -12,7 +13,9 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(setup)
             .add_startup_system(configure_visuals)
-            .add_system(ui);
+            .add_system(ui)
+            .add_system(inventory)
+            .add_system(hud);
     }
 }
 
@@ -68,6 +71,123 @@ fn ui(mut egui_ctx: ResMut<EguiContext>, windows: Res<Windows>, log: Res<Log>) {
         });
 }
 
+fn inventory(
+    mut egui_ctx: ResMut<EguiContext>,
+    inventory: Res<Inventory>,
+    events: Res<ReshockEvents>,
+    mut actions: EventWriter<ActionEvent>,
+) {
+    use api::action_request::{Action, DropAction, EquipAction, UnequipAction};
+
+    let margin = 5.0;
+
+    egui::Window::new("Inventory")
+        .anchor(Align2::RIGHT_TOP, [-margin, margin])
+        .resizable(false)
+        .frame(egui::Frame {
+            stroke: (2.0, egui::Color32::GRAY).into(),
+            fill: egui::Color32::from_rgba_premultiplied(0, 0, 0, 204),
+            inner_margin: 5.0.into(),
+            ..default()
+        })
+        .show(egui_ctx.ctx_mut(), |ui| {
+            // Buttons only act once the events of the last action have played out
+            ui.set_enabled(events.queue.is_empty());
+
+            egui::Grid::new("items").show(ui, |ui| {
+                for item in inventory.0.iter() {
+                    ui.label(RichText::new(&item.name).size(16.0));
+
+                    let toggle = if item.equipped {
+                        ui.button("Unequip")
+                            .clicked()
+                            .then_some(Action::Unequip(UnequipAction { item: item.entity }))
+                    } else {
+                        ui.button("Equip")
+                            .clicked()
+                            .then_some(Action::Equip(EquipAction { item: item.entity }))
+                    };
+
+                    let drop = ui
+                        .button("Drop")
+                        .clicked()
+                        .then_some(Action::Drop(DropAction { item: item.entity }));
+
+                    if let Some(action) = toggle.or(drop) {
+                        actions.send(ActionEvent(action));
+                    }
+
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn hud(mut egui_ctx: ResMut<EguiContext>, status: Res<PlayerStatus>) {
+    let status = match &status.0 {
+        Some(it) => it,
+        None => return,
+    };
+
+    let margin = 5.0;
+
+    egui::Window::new("Status")
+        .anchor(Align2::LEFT_TOP, [margin, margin])
+        .resizable(false)
+        .title_bar(false)
+        .frame(egui::Frame {
+            stroke: (2.0, egui::Color32::GRAY).into(),
+            fill: egui::Color32::from_rgba_premultiplied(0, 0, 0, 204),
+            inner_margin: 5.0.into(),
+            ..default()
+        })
+        .show(egui_ctx.ctx_mut(), |ui| {
+            let bar = |value: u32, max: u32, text: String| {
+                egui::ProgressBar::new(value as f32 / max.max(1) as f32)
+                    .desired_width(160.0)
+                    .text(RichText::new(text).size(16.0))
+            };
+
+            egui::Grid::new("status").show(ui, |ui| {
+                ui.label(RichText::new("Health").size(16.0));
+                ui.add(bar(
+                    status.hp,
+                    status.max_hp,
+                    format!("{}/{}", status.hp, status.max_hp),
+                ));
+                ui.end_row();
+
+                ui.label(RichText::new("Battery").size(16.0));
+                ui.add(bar(
+                    status.charge,
+                    status.max_charge,
+                    format!("{}/{}", status.charge, status.max_charge),
+                ));
+                ui.end_row();
+
+                for weapon in status.weapons.iter() {
+                    ui.label(RichText::new("Weapon").size(16.0));
+                    ui.label(RichText::new(weapon).size(16.0));
+                    ui.end_row();
+                }
+
+                if let Some(api::Ammo { loaded, spare }) = status.ammo {
+                    ui.label(RichText::new("Ammo").size(16.0));
+                    ui.label(RichText::new(format!("{} + {}", loaded, spare)).size(16.0));
+                    ui.end_row();
+                }
+            });
+
+            if status.god {
+                ui.label(
+                    RichText::new("God mode")
+                        .size(16.0)
+                        .color(egui::Color32::GOLD),
+                );
+            }
+        });
+}
+
 fn configure_visuals(mut egui_ctx: ResMut<EguiContext>) {
     egui_ctx.ctx_mut().set_visuals(egui::Visuals {
         window_rounding: Rounding::none(),
@@ -86,3 +86,15 @@ impl Log {
 
 #[derive(Default)]
 pub struct Cursor(pub Option<IVec2>);
+
+/// Items carried by the player, as of the last state update.
+#[derive(Default)]
+pub struct Inventory(pub Vec<api::InventoryItem>);
+
+/// Status of the player, as of the last state update.
+#[derive(Default)]
+pub struct PlayerStatus(pub Option<api::PlayerStatus>);
+
+/// Version of the state the entities reflect, for state diffs to apply to.
+#[derive(Default)]
+pub struct StateVersion(pub u64);
@@ -13,9 +13,13 @@ const APPENDAGES_SOUND: &'static str = "sshock/sounds/00256.wav";
 pub fn system(
     mut commands: Commands,
     mut reader: EventReader<api::HitEvent>,
+    player: Query<&Position, With<Player>>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
 ) {
+    // Hit positions are relative to the player
+    let origin = Player::origin(&player);
+
     for api::HitEvent {
         kind,
         direction,
@@ -46,7 +50,7 @@ pub fn system(
         };
 
         commands.spawn_bundle(bundle::Effect {
-            position: Position(ivec2(x, y)),
+            position: Position(origin + ivec2(x, y)),
             renderable: Renderable {
                 char,
                 ordering: Ordering::Effect,
@@ -1,71 +1,136 @@
 use api::action_request::*;
-use api::reshock_client::ReshockClient;
-use bevy::log;
 use bevy::prelude::*;
-use tokio::runtime::Runtime;
-use tonic::transport::Channel;
+use bevy::utils::HashMap;
 
-use crate::plugin::RestartEvent;
+use crate::component::{Enemy, Marker, Player, Position, ReshockEntity};
+use crate::keymap::{Binding, Keymap, POWER_KEYS};
+use crate::plugin::{ActionEvent, RestartEvent};
 use crate::resource::ReshockEvents;
 
 pub fn system(
     keys: Res<Input<KeyCode>>,
-    runtime: Res<Runtime>,
-    mut client: ResMut<ReshockClient<Channel>>,
-    mut events: ResMut<ReshockEvents>,
+    keymap: Res<Keymap>,
+    events: Res<ReshockEvents>,
     mut writer: EventWriter<RestartEvent>,
+    mut actions: EventWriter<ActionEvent>,
+    mut marker: Query<&mut Marker>,
+    enemies: Query<(&ReshockEntity, &Position), With<Enemy>>,
+    player: Query<&Position, With<Player>>,
 ) {
     if !events.queue.is_empty() {
         return;
     }
 
+    let origin = Player::origin(&player);
+
     let shift = keys.pressed(KeyCode::RShift) || keys.pressed(KeyCode::LShift);
 
-    if shift && keys.just_pressed(KeyCode::R) {
+    if shift && keys.just_pressed(keymap.restart()) {
         writer.send(RestartEvent);
         return;
     }
 
-    if let Some(action) = if keys.just_pressed(KeyCode::A) {
-        Some(Action::Dwim(DwimAction::Left as i32))
-    } else if keys.just_pressed(KeyCode::E) {
-        Some(Action::Dwim(DwimAction::Right as i32))
-    } else if keys.just_pressed(KeyCode::Comma) {
-        Some(Action::Dwim(DwimAction::Up as i32))
-    } else if keys.just_pressed(KeyCode::Q) {
-        Some(Action::Dwim(DwimAction::Down as i32))
-    } else if keys.just_pressed(KeyCode::Apostrophe) {
-        Some(Action::Dwim(DwimAction::UpLeft as i32))
-    } else if keys.just_pressed(KeyCode::Period) {
-        Some(Action::Dwim(DwimAction::UpRight as i32))
-    } else if keys.just_pressed(KeyCode::Semicolon) {
-        Some(Action::Dwim(DwimAction::DownLeft as i32))
-    } else if keys.just_pressed(KeyCode::J) {
-        Some(Action::Dwim(DwimAction::DownRight as i32))
-    } else if keys.just_pressed(KeyCode::C) {
-        Some(Action::Dwim(DwimAction::Close as i32))
-    } else if keys.just_pressed(KeyCode::F) {
-        Some(Action::Dwim(DwimAction::Shoot as i32))
-    } else if keys.just_pressed(KeyCode::G) {
-        Some(Action::God(GodModeAction {}))
-    } else {
-        None
-    } {
-        runtime.block_on(async move {
-            match client
-                .process_action(api::ActionRequest {
-                    action: Some(action),
-                })
-                .await
-            {
-                Ok(response) => {
-                    events.queue = response.into_inner().events.into();
-                    log::debug!("Received event queue {}", *events);
-                }
-                Err(e) => {
-                    log::warn!("Couldn't process action {}", e);
-                }
+    let mut marker = marker.single_mut();
+    let binding = keymap.just_pressed(&keys);
+
+    // While targeting, keys only cycle through and confirm targets
+    if let Marker::Target(id) = *marker {
+        if keys.just_pressed(KeyCode::Tab) || binding == Some(Binding::Target) {
+            if let Some(next) = next_target(&enemies, origin, Some(id)) {
+                *marker = Marker::Target(next);
+            }
+        } else if keys.just_pressed(KeyCode::Back) {
+            *marker = Marker::Selection;
+        } else if keys.just_pressed(KeyCode::Return)
+            || binding == Some(Binding::Dwim(DwimAction::Shoot))
+        {
+            *marker = Marker::Selection;
+            let action = Action::Shoot(ShootAction {
+                target: Some(shoot_action::Target::Entity(id)),
+            });
+            actions.send(ActionEvent(action));
+        }
+
+        return;
+    }
+
+    // While looking around, keys only move the marker
+    if let Marker::Look(pos) = *marker {
+        if binding == Some(Binding::Look) || keys.just_pressed(KeyCode::Back) {
+            *marker = Marker::Selection;
+        } else if let Some(direction) = binding.and_then(|it| it.direction()) {
+            *marker = Marker::Look(pos + direction);
+        }
+
+        return;
+    }
+
+    if let Some(action) = match binding {
+        Some(Binding::Dwim(DwimAction::Shoot)) => match *marker {
+            Marker::Active(pos) => {
+                *marker = Marker::Selection;
+                // Targets are relative to the player
+                let pos = pos - origin;
+                Some(Action::Shoot(ShootAction {
+                    target: Some(shoot_action::Target::Position(api::Position {
+                        x: pos.x,
+                        y: pos.y,
+                    })),
+                }))
+            }
+            _ => Some(Action::Dwim(DwimAction::Shoot as i32)),
+        },
+        Some(Binding::Dwim(action)) => Some(Action::Dwim(action as i32)),
+        Some(Binding::Target) => {
+            if let Some(id) = next_target(&enemies, origin, None) {
+                *marker = Marker::Target(id);
             }
-        });
+            None
+        }
+        Some(Binding::Look) => {
+            *marker = Marker::Look(origin);
+            None
+        }
+        Some(Binding::Reload) => Some(Action::Reload(ReloadAction {})),
+        Some(Binding::Eject) => Some(Action::Eject(EjectMagazineAction {})),
+        Some(Binding::PickUp) => Some(Action::PickUp(PickUpAction { item: None })),
+        Some(Binding::God) => Some(Action::God(GodModeAction {})),
+        Some(Binding::Map | Binding::Restart) => None,
+        None => POWER_KEYS
+            .iter()
+            .position(|key| keys.just_pressed(*key))
+            .map(|power| {
+                Action::Power(PowerAction {
+                    power: power as u32 + 1,
+                })
+            }),
+    } {
+        actions.send(ActionEvent(action));
     }
 }
+
+/// The enemy in sight to target after `current`, closest to the player at
+/// `origin` first. An enemy shown more than once only counts once, by its
+/// closest position; this only orders the cycle, the server picks where a
+/// shot aims.
+fn next_target(
+    enemies: &Query<(&ReshockEntity, &Position), With<Enemy>>,
+    origin: IVec2,
+    current: Option<u32>,
+) -> Option<u32> {
+    let mut distances: HashMap<u32, i32> = HashMap::default();
+    for (entity, Position(pos)) in enemies.iter() {
+        let delta = *pos - origin;
+        let distance = distances.entry(entity.0).or_insert(i32::MAX);
+        *distance = (*distance).min(delta.dot(delta));
+    }
+
+    let mut targets: Vec<_> = distances.into_iter().map(|(id, d)| (d, id)).collect();
+    targets.sort();
+
+    let next = current
+        .and_then(|id| targets.iter().position(|(_, it)| *it == id))
+        .map_or(0, |i| i + 1);
+
+    targets.get(next).or(targets.first()).map(|(_, id)| *id)
+}
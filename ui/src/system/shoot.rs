@@ -2,15 +2,18 @@ use api::shoot_event::*;
 use bevy::{math::vec2, prelude::*};
 use bevy_kira_audio::Audio;
 
+use crate::component::{Player, Position};
 use crate::{bundle, resource::*};
 
 const ASSAULT_RIFLE_SOUND: &'static str = "sshock/sounds/00218.wav";
+const ION_RIFLE_SOUND: &'static str = "sshock/sounds/00227.wav";
 const BULLET_SPEED: f32 = 2000.0;
 const BULLET_SIZE: f32 = 10.0;
 
 pub fn system(
     mut commands: Commands,
     mut reader: EventReader<api::ShootEvent>,
+    player: Query<&Position, With<Player>>,
     dimensions: Res<TileDimensions>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
@@ -21,6 +24,9 @@ pub fn system(
         None => return,
     };
 
+    // Shot positions are relative to the player
+    let origin = Player::origin(&player);
+
     for api::ShootEvent {
         source,
         target,
@@ -29,17 +35,24 @@ pub fn system(
     } in reader.iter()
     {
         let start = match source {
-            Some(pos) => vec2(pos.x as f32 * width, pos.y as f32 * height),
+            Some(pos) => vec2(
+                (origin.x + pos.x) as f32 * width,
+                (origin.y + pos.y) as f32 * height,
+            ),
             None => continue,
         };
 
         let end = match target {
-            Some(pos) => vec2(pos.x as f32 * width, pos.y as f32 * height),
+            Some(pos) => vec2(
+                (origin.x + pos.x) as f32 * width,
+                (origin.y + pos.y) as f32 * height,
+            ),
             None => continue,
         };
 
         if let Some(sound) = match ShootSound::from_i32(*sound) {
             Some(ShootSound::Mark3AssaultRifle) => Some(ASSAULT_RIFLE_SOUND),
+            Some(ShootSound::IonRifle) => Some(ION_RIFLE_SOUND),
             _ => None,
         } {
             audio.play(asset_server.load(sound));
@@ -54,6 +67,9 @@ pub fn system(
                     BULLET_SIZE,
                 ));
             }
+            Some(ShootKind::Beam) => {
+                commands.spawn_bundle(bundle::Beam::new(start, end));
+            }
             _ => {
                 events.transitions -= 1;
             }
@@ -5,158 +5,223 @@ use bevy::math::ivec2;
 use bevy::prelude::*;
 use bevy::utils::HashSet;
 
+use crate::bundle;
 use crate::component::{Position, *};
-use crate::{bundle, resource::*};
+use crate::plugin::DumpStateEvent;
+use crate::resource::{Inventory, PlayerStatus, ReshockEvents, ReshockFont, StateVersion};
 
 pub fn system(
     mut commands: Commands,
-    entities: Query<Entity, With<ReshockEntity>>,
+    entities: Query<(Entity, &ReshockEntity)>,
     tiles: Query<&Position, With<Tile>>,
     mut reader: EventReader<api::StateUpdateEvent>,
+    mut dump: EventWriter<DumpStateEvent>,
     mut events: ResMut<ReshockEvents>,
+    mut inventory: ResMut<Inventory>,
+    mut status: ResMut<PlayerStatus>,
+    mut version: ResMut<StateVersion>,
     font: Res<ReshockFont>,
 ) {
-    for api::StateUpdateEvent { player, state } in reader.iter() {
-        let state = match state {
-            Some(it) => it,
-            None => {
+    let mut tiles: HashSet<_> = tiles.iter().copied().collect();
+
+    for api::StateUpdateEvent {
+        player,
+        state,
+        diff,
+    } in reader.iter()
+    {
+        let changed = match (state, diff) {
+            (Some(state), _) => {
+                for (entity, _) in entities.iter() {
+                    commands.entity(entity).despawn();
+                }
+
+                inventory.0 = state.inventory.clone();
+                status.0 = state.status.clone();
+                version.0 = state.version;
+
+                Some(&state.entities)
+            }
+            (None, Some(diff)) if diff.base == version.0 => {
+                let ids: HashSet<_> = diff.removed.iter().chain(diff.entities.keys()).collect();
+                for (entity, id) in entities.iter() {
+                    if ids.contains(&id.0) {
+                        commands.entity(entity).despawn();
+                    }
+                }
+
+                inventory.0 = diff.inventory.clone();
+                status.0 = diff.status.clone();
+                version.0 = diff.version;
+
+                Some(&diff.entities)
+            }
+            // Already part of a full state received in the meantime
+            (None, Some(diff)) if diff.version <= version.0 => None,
+            (None, Some(diff)) => {
+                log::warn!(
+                    "State diff for version {} doesn't apply to version {}, reloading state",
+                    diff.base,
+                    version.0
+                );
+                dump.send(DumpStateEvent);
+                None
+            }
+            (None, None) => {
                 log::error!("Received empty state from Reshock");
-                return;
+                None
             }
         };
 
-        for entity in entities.iter() {
-            commands.entity(entity).despawn();
+        for (entity, components) in changed.into_iter().flatten() {
+            spawn(
+                &mut commands,
+                &mut tiles,
+                &font,
+                *player,
+                *entity,
+                components.clone(),
+            );
         }
 
-        let tiles: HashSet<_> = tiles.iter().collect();
-
-        for (entity, components) in state.entities.clone() {
-            let Components {
-                positions,
-                renderable,
-                door,
-                memory,
-                wall,
-            } = components;
-
-            for PositionComponent { x, y } in positions {
-                let pos = Position(ivec2(x, y));
-                if !tiles.contains(&pos) {
-                    commands.spawn_bundle(bundle::Tile::new(pos, &font));
-                }
+        events.transitions -= 1;
+    }
+}
 
-                let mut e = commands.spawn();
+/// Spawns one entity per position of the Reshock `entity`.
+fn spawn(
+    commands: &mut Commands,
+    tiles: &mut HashSet<Position>,
+    font: &ReshockFont,
+    player: u32,
+    entity: u32,
+    components: Components,
+) {
+    let Components {
+        positions,
+        renderable,
+        door,
+        memory,
+        wall,
+    } = components;
 
-                if entity == *player {
-                    e.insert(Player);
-                    e.insert(Focus);
-                }
+    for PositionComponent { x, y } in positions {
+        let pos = Position(ivec2(x, y));
+        if tiles.insert(pos) {
+            commands.spawn_bundle(bundle::Tile::new(pos, font));
+        }
 
-                e.insert(ReshockEntity(entity));
-                e.insert(Position(ivec2(x, y)));
-
-                let memory = memory.is_some();
-
-                if let Some(RenderableComponent { renderable }) = renderable {
-                    if let Some(renderable) = match ApiRenderable::from_i32(renderable) {
-                        Some(ApiRenderable::None) => Some(Renderable::default()),
-                        Some(ApiRenderable::Wall) => Some(Renderable {
-                            char: ' ',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::rgb(0.169, 0.173, 0.29)
-                            },
-                            ordering: Ordering::Wall,
-                        }),
-                        Some(ApiRenderable::WallSwitch) => Some(Renderable {
-                            char: '⌖',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::rgb(0.169, 0.173, 0.29)
-                            },
-                            ordering: Ordering::Actor,
-                        }),
-                        Some(ApiRenderable::Door) => Some(Renderable {
-                            char: ' ',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::WHITE
-                            },
-                            ordering: Ordering::Door,
-                        }),
-                        Some(ApiRenderable::Human) => Some(Renderable {
-                            char: '@',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::WHITE
-                            },
-                            ordering: Ordering::Actor,
-                        }),
-                        Some(ApiRenderable::ServBot) => Some(Renderable {
-                            char: 'b',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::ORANGE_RED
-                            },
-                            ordering: Ordering::Actor,
-                        }),
-                        Some(ApiRenderable::Floor) => Some(Renderable {
-                            char: '·',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::rgb(0.169, 0.173, 0.29)
-                            },
-                            ordering: Ordering::Floor,
-                        }),
-                        Some(ApiRenderable::Corpse) => Some(Renderable {
-                            char: '%',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::WHITE
-                            },
-                            ordering: Ordering::Item,
-                        }),
-                        Some(ApiRenderable::Server) => Some(Renderable {
-                            char: 'c',
-                            color: if memory {
-                                Color::DARK_GRAY
-                            } else {
-                                Color::WHITE
-                            },
-                            ordering: Ordering::Actor,
-                        }),
-                        _ => None,
-                    } {
-                        e.insert(renderable);
-                    }
-                }
+        let mut e = commands.spawn();
 
-                if let Some(DoorComponent { open }) = door {
-                    e.insert(Door {
-                        open,
-                        open_color: Color::DARK_GRAY,
-                        close_color: Color::WHITE,
-                    });
-                }
+        if entity == player {
+            e.insert(Player);
+            e.insert(Focus);
+        }
 
-                if let Some(WallComponent {}) = wall {
-                    e.insert(Wall);
-                }
+        e.insert(ReshockEntity(entity));
+        e.insert(Position(ivec2(x, y)));
 
-                if memory {
-                    e.insert(Memory);
-                }
+        let memory = memory.is_some();
+
+        if let Some(RenderableComponent { renderable }) = renderable {
+            if let Some(renderable) = match ApiRenderable::from_i32(renderable) {
+                Some(ApiRenderable::None) => Some(Renderable::default()),
+                Some(ApiRenderable::Wall) => Some(Renderable {
+                    char: ' ',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::rgb(0.169, 0.173, 0.29)
+                    },
+                    ordering: Ordering::Wall,
+                }),
+                Some(ApiRenderable::WallSwitch) => Some(Renderable {
+                    char: '⌖',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::rgb(0.169, 0.173, 0.29)
+                    },
+                    ordering: Ordering::Actor,
+                }),
+                Some(ApiRenderable::Door) => Some(Renderable {
+                    char: ' ',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::WHITE
+                    },
+                    ordering: Ordering::Door,
+                }),
+                Some(ApiRenderable::Human) => Some(Renderable {
+                    char: '@',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::WHITE
+                    },
+                    ordering: Ordering::Actor,
+                }),
+                Some(ApiRenderable::ServBot) => Some(Renderable {
+                    char: 'b',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::ORANGE_RED
+                    },
+                    ordering: Ordering::Actor,
+                }),
+                Some(ApiRenderable::Floor) => Some(Renderable {
+                    char: '·',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::rgb(0.169, 0.173, 0.29)
+                    },
+                    ordering: Ordering::Floor,
+                }),
+                Some(ApiRenderable::Corpse) => Some(Renderable {
+                    char: '%',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::WHITE
+                    },
+                    ordering: Ordering::Item,
+                }),
+                Some(ApiRenderable::Server) => Some(Renderable {
+                    char: 'c',
+                    color: if memory {
+                        Color::DARK_GRAY
+                    } else {
+                        Color::WHITE
+                    },
+                    ordering: Ordering::Actor,
+                }),
+                _ => None,
+            } {
+                e.insert(renderable);
             }
         }
 
-        events.transitions -= 1;
+        if let Some(DoorComponent { open }) = door {
+            e.insert(Door {
+                open,
+                open_color: Color::DARK_GRAY,
+                close_color: Color::WHITE,
+            });
+        }
+
+        if let Some(WallComponent {}) = wall {
+            e.insert(Wall);
+        }
+
+        if memory {
+            e.insert(Memory);
+        } else if let Some(RenderableComponent { renderable }) = renderable
+            && ApiRenderable::from_i32(renderable) == Some(ApiRenderable::ServBot)
+        {
+            e.insert(Enemy);
+        }
     }
 }